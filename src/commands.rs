@@ -0,0 +1,380 @@
+use crate::{
+  editor,
+  lib::{
+    enums::{DayOfWeek, ExerciseType},
+    export::{ics, Format},
+    file_format::FileFormat,
+    history::{tcx, transfer, Session},
+    settings::Settings,
+    util::prompt,
+    workout::{
+      edit::Edits,
+      external,
+      filter::Filter,
+      importer::{Conflict, Importer},
+      search,
+      workout_list::WorkoutList,
+      Start, Workout, WorkoutImport,
+    },
+  },
+  sessions::do_workout,
+};
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate, NaiveTime, Utc};
+use std::path::{Path, PathBuf};
+use workout_paths::data_path;
+
+/// `workout run <title> [--from-set N]` - run a workout straight away,
+/// optionally starting from a (1 based) set.
+pub fn run(title: &str, from_set: Option<usize>) -> Result<()> {
+  let (workouts, _) = Workout::load_dir(&data_path())?;
+  let workout =
+    match workouts.iter().find(|w| w.title.eq_ignore_ascii_case(title)) {
+      Some(workout) => workout,
+      None => bail!("There is no workout called {:?}", title),
+    };
+
+  let start = match from_set {
+    Some(set) if set == 0 || set > workout.sets.len() => bail!(
+      "{:?} has {} sets - pick a set from 1 to {}",
+      workout.title,
+      workout.sets.len(),
+      workout.sets.len()
+    ),
+    Some(set) => Start::Set(set - 1),
+    None => Start::Screen(0),
+  };
+  do_workout(workout, start)
+}
+
+/// `workout search <query>` - print the workouts matching the query, best
+/// matches first.
+pub fn search(query: &str) -> Result<()> {
+  let (workouts, errors) = Workout::load_dir(&data_path())?;
+  for (path, e) in errors {
+    eprintln!("Error loading workout file {:?}: {}", path, e);
+  }
+
+  let results = search::search(&workouts, query);
+  if results.is_empty() {
+    println!("No workouts match {:?}", query);
+  }
+  for result in results {
+    println!("{}  ({})", result.workout.title, result.field);
+  }
+  Ok(())
+}
+
+/// `workout list [filters]` - print the workouts matching the filter.
+pub fn list(filter: &Filter) -> Result<()> {
+  let (workouts, errors) = Workout::load_dir(&data_path())?;
+  for (path, e) in errors {
+    eprintln!("Error loading workout file {:?}: {}", path, e);
+  }
+  let sessions = Session::load_all()?;
+
+  let matching = workouts.query(filter, &sessions);
+  if matching.is_empty() {
+    println!("No workouts match {}", filter);
+  }
+  for workout in matching {
+    println!(
+      "{}  ({}, {}, {} minutes)",
+      workout.title,
+      workout.day,
+      workout.workout_type,
+      workout.duration().as_secs() / 60
+    );
+  }
+  Ok(())
+}
+
+/// `workout export` - write a workout, or a booklet of every workout, to a
+/// file. Without `--output` the file is named after the workout and written to
+/// the current directory.
+pub fn export(
+  title: Option<&str>,
+  format: Format,
+  output: Option<PathBuf>,
+) -> Result<()> {
+  let (workouts, _) = Workout::load_dir(&data_path())?;
+  let (chosen, name): (Vec<&Workout>, &str) = match title {
+    Some(title) => {
+      match workouts.iter().find(|w| w.title.eq_ignore_ascii_case(title)) {
+        Some(workout) => (vec![workout], &workout.title),
+        None => bail!("There is no workout called {:?}", title),
+      }
+    }
+    None if workouts.is_empty() => bail!("There are no workouts to export"),
+    None => (workouts.iter().collect(), "workouts"),
+  };
+
+  let path = output.unwrap_or_else(|| {
+    PathBuf::from(format!("{}.{}", name, format.extension()))
+  });
+  std::fs::write(&path, format.render(&chosen))?;
+  println!("Exported to {:?}", path);
+  Ok(())
+}
+
+/// `workout history export` - write the sessions done since a day, or every
+/// session, to a file or to stdout.
+pub fn history_export(
+  format: transfer::Format,
+  since: Option<NaiveDate>,
+  intervals: bool,
+  output: Option<PathBuf>,
+) -> Result<()> {
+  let sessions = Session::load_all()?;
+  let sessions = transfer::since(&sessions, since);
+  let text = match format {
+    transfer::Format::Csv => transfer::to_csv(&sessions, intervals),
+    transfer::Format::Json => transfer::to_json(&sessions)?,
+  };
+
+  match output {
+    Some(path) => {
+      std::fs::write(&path, text)?;
+      eprintln!("Exported {} sessions to {:?}", sessions.len(), path);
+    }
+    None => print!("{}", text),
+  }
+  Ok(())
+}
+
+/// `workout history import <file>` - add the sessions in a JSON export that
+/// aren't in the history already.
+pub fn history_import(path: &Path) -> Result<()> {
+  if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv")) {
+    bail!(
+      "CSV exports leave out lifts and intervals - export the history with \
+       --format json to import it"
+    );
+  }
+  let imported = transfer::from_json(&std::fs::read_to_string(path)?)?;
+  let count = imported.len();
+  let new = transfer::new_sessions(&Session::load_all()?, imported);
+  let mut saved = 0;
+  for session in new.iter() {
+    match session.save() {
+      Ok(()) => saved += 1,
+      Err(e) => println!("Couldn't import {}: {}", session.title, e),
+    }
+  }
+  println!(
+    "Imported {} sessions, {} were already in the history",
+    saved,
+    count - new.len()
+  );
+  Ok(())
+}
+
+/// `workout history tcx` - write the last session, or every session since a
+/// day, as TCX files.
+pub fn history_tcx(
+  since: Option<NaiveDate>,
+  output: Option<PathBuf>,
+) -> Result<()> {
+  let sessions = Session::load_all()?;
+  let sessions = match since {
+    Some(_) => transfer::since(&sessions, since),
+    None => sessions.last().into_iter().collect(),
+  };
+  if sessions.is_empty() {
+    bail!("There are no sessions to export");
+  }
+
+  let dir = output.unwrap_or_else(|| PathBuf::from("."));
+  std::fs::create_dir_all(&dir)?;
+  for session in sessions {
+    let path = dir.join(tcx::file_name(session));
+    std::fs::write(&path, tcx::render(session))?;
+    println!("Exported {} to {:?}", session.title, path);
+  }
+  Ok(())
+}
+
+/// `workout calendar export` - write a calendar with a weekly event for every
+/// workout, at the time from the settings unless another is given.
+pub fn calendar_export(
+  time: Option<NaiveTime>,
+  output: Option<PathBuf>,
+) -> Result<()> {
+  let (workouts, _) = Workout::load_dir(&data_path())?;
+  if workouts.is_empty() {
+    bail!("There are no workouts to put in the calendar");
+  }
+  let time = match time {
+    Some(time) => time,
+    None => Settings::load()?.workout_time,
+  };
+
+  let workouts: Vec<&Workout> = workouts.iter().collect();
+  let path = output.unwrap_or_else(|| PathBuf::from("workouts.ics"));
+  let today = Local::now().date_naive();
+  std::fs::write(&path, ics::render(&workouts, time, today, Utc::now()))?;
+  println!("Exported {} workouts to {:?}", workouts.len(), path);
+  Ok(())
+}
+
+/// Ask what to do with an imported workout whose title is already taken.
+pub fn ask_conflict(title: &str) -> Result<Conflict> {
+  loop {
+    let answer = prompt(&format!(
+      "{:?} already exists - (o)verwrite, (r)ename or (s)kip? ",
+      title
+    ))?;
+    if let Ok(conflict) = answer.parse() {
+      return Ok(conflict);
+    }
+  }
+}
+
+/// `workout import [--on-conflict overwrite|rename|skip] [--convert]
+/// [<file>...]` - import workout files, or rewrite import files that use the
+/// old nested lists in the new form.
+pub fn import(
+  convert: bool,
+  on_conflict: Option<Conflict>,
+  paths: Vec<PathBuf>,
+) -> Result<()> {
+  let importer = Importer::new()?;
+  let paths = if paths.is_empty() { importer.pending()? } else { paths };
+
+  if !convert {
+    let report = importer.run(&paths, |title| match on_conflict {
+      Some(conflict) => Ok(conflict),
+      None => ask_conflict(title),
+    })?;
+    print!("{}", report);
+    if !report.failed.is_empty() {
+      bail!("{} of the files couldn't be imported", report.failed.len());
+    }
+    return Ok(());
+  }
+
+  for path in paths {
+    let workout = match WorkoutImport::load_file(&path) {
+      Ok(workout) => workout,
+      Err(e) => {
+        eprintln!("Couldn't read {:?}: {}", path, e);
+        continue;
+      }
+    };
+    if workout.is_old_form() {
+      FileFormat::from_path(&path)?.write(&path, &workout.convert())?;
+      println!("Converted {:?}", path);
+    } else {
+      println!("{:?} is already in the new form", path);
+    }
+  }
+  Ok(())
+}
+
+/// `workout edit [--external] <title>` - edit a workout in the menus or in
+/// `$EDITOR`, starting a new one if there's no workout with the title.
+pub fn edit(title: &str, external: bool) -> Result<()> {
+  let (workouts, _) = Workout::load_dir(&data_path())?;
+  let (workout, original) =
+    match workouts.into_iter().find(|w| w.title == title) {
+      Some(workout) => (workout, Some(title)),
+      // the editor has to start from something that's already valid.
+      None if external => {
+        (Workout { title: title.to_string(), ..Workout::default() }, None)
+      }
+      None => (
+        Workout::new(
+          title,
+          None,
+          DayOfWeek::today(),
+          ExerciseType::LowerBodyAbs,
+          vec![],
+        ),
+        None,
+      ),
+    };
+  if external {
+    if !external::edit(&workout, original)? {
+      println!("Nothing was changed.");
+    }
+  } else {
+    editor::edit(workout, original.map(str::to_string))?;
+  }
+  Ok(())
+}
+
+/// `workout edit --undo` - put back what the last save from the editor
+/// replaced.
+pub fn undo() -> Result<()> {
+  match Edits::new()?.undo()? {
+    Some(done) => println!("{}", done),
+    None => println!("There's nothing to undo."),
+  }
+  Ok(())
+}
+
+/// `workout duplicate <title> --as <new title> [--edit]` - save a copy of a
+/// workout, and with `--edit` open the copy in the editor.
+pub fn duplicate(title: &str, new_title: &str, edit: bool) -> Result<()> {
+  let copy = Edits::new()?.duplicate(title, new_title)?;
+  println!("Copied {:?} to {:?}", title, new_title);
+  if edit {
+    editor::edit(copy, Some(new_title.to_string()))?;
+  }
+  Ok(())
+}
+
+/// `workout rename <title> --to <new title>` - give a workout a new title.
+pub fn rename(title: &str, new_title: &str) -> Result<()> {
+  Edits::new()?.rename(title, new_title)?;
+  println!("Renamed {:?} to {:?}", title, new_title);
+  Ok(())
+}
+
+/// `workout delete <title>` - move a workout to the trash.
+pub fn delete(title: &str) -> Result<()> {
+  Edits::new()?.delete(title)?;
+  println!(
+    "Moved {:?} to the trash - `workout restore {}` puts it back",
+    title, title
+  );
+  Ok(())
+}
+
+/// `workout trash` - list the workouts in the trash.
+pub fn trash() -> Result<()> {
+  let titles = Edits::new()?.trashed()?;
+  if titles.is_empty() {
+    println!("The trash is empty.");
+  }
+  for title in titles {
+    println!("{}", title);
+  }
+  Ok(())
+}
+
+/// `workout restore <title>` - move a workout back out of the trash.
+pub fn restore(title: &str) -> Result<()> {
+  Edits::new()?.restore(title)?;
+  println!("Restored {:?}", title);
+  Ok(())
+}
+
+/// `workout upgrade` - save workout files written by an older version in the
+/// current structure.
+pub fn upgrade() -> Result<()> {
+  let (upgraded, errors) = Workout::upgrade_dir(&data_path())?;
+  for path in &upgraded {
+    println!("Upgraded {:?}", path);
+  }
+  if upgraded.is_empty() {
+    println!("Every workout file is up to date.");
+  }
+  for (path, e) in &errors {
+    eprintln!("Couldn't upgrade {:?}: {}", path, e);
+  }
+  if !errors.is_empty() {
+    bail!("{} of the files couldn't be upgraded", errors.len());
+  }
+  Ok(())
+}
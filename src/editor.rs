@@ -0,0 +1,394 @@
+use crate::lib::{
+  enums::{DayOfWeek, ExerciseType},
+  menu::{Menu, Selection},
+  util::{clear_screen, pause, prompt},
+  workout::{
+    edit::Edits,
+    exercise::{Exercise, ExerciseSet},
+    prescription::Prescription,
+    Workout,
+  },
+};
+use anyhow::Result;
+use std::{fmt::Display, str::FromStr};
+
+/// A part of the workout to change.
+#[derive(Copy, Clone)]
+enum Edit {
+  Title,
+  Day,
+  Type,
+  Warmup,
+  Link,
+  Tags,
+  /// The set with this (0 based) index.
+  Set(usize),
+  AddSet,
+  Save,
+}
+
+/// A change to a set.
+#[derive(Copy, Clone)]
+enum SetEdit {
+  /// The exercise with this (0 based) index.
+  Exercise(usize),
+  AddExercise,
+  Type,
+  MoveUp,
+  MoveDown,
+  Remove,
+}
+
+/// A change to an exercise.
+#[derive(Copy, Clone)]
+enum ExerciseEdit {
+  Name,
+  Description,
+  Timing,
+  Equipment,
+  MoveUp,
+  MoveDown,
+  Remove,
+}
+
+/// What to do with unsaved changes when leaving the editor.
+enum Leave {
+  Save,
+  Discard,
+}
+
+/// Edit a workout until the user goes back, asking what to do with any
+/// unsaved changes. `original` is the title the workout was saved under, or
+/// `None` for a new workout. Returns whether anything was saved.
+pub fn edit(mut workout: Workout, original: Option<String>) -> Result<bool> {
+  let edits = Edits::new()?;
+  let mut original = original;
+  // a new workout is left without asking until something's been changed.
+  let mut last_saved = workout.clone();
+  let mut saved = false;
+
+  loop {
+    let changed = last_saved != workout;
+    let unsaved = changed || original.is_none();
+    let edit = match editor_menu(&workout, unsaved).run() {
+      Selection::Chose(edit) => edit,
+      _ if !changed => return Ok(saved),
+      _ => {
+        let menu = Menu::new("Save your changes?")
+          .line(format!(
+            "{:?} has changes that haven't been saved.",
+            workout.title
+          ))
+          .without_quit()
+          .item("Save", || Leave::Save)
+          .item("Throw them away", || Leave::Discard);
+        match menu.run() {
+          Selection::Chose(Leave::Save) => {
+            if save(&edits, &workout, original.as_deref())? {
+              return Ok(true);
+            }
+            continue;
+          }
+          Selection::Chose(Leave::Discard) => return Ok(saved),
+          _ => continue,
+        }
+      }
+    };
+
+    match edit {
+      Edit::Title => workout.title = ask("Title", &workout.title)?,
+      Edit::Day => {
+        if let Some(day) = pick("Which day?", &DayOfWeek::VALUES) {
+          workout.day = day;
+        }
+      }
+      Edit::Type => {
+        if let Some(workout_type) =
+          pick("What type of workout?", &ExerciseType::VALUES)
+        {
+          workout.workout_type = workout_type;
+        }
+      }
+      Edit::Warmup => {
+        workout.warmup_length =
+          ask_number("Warmup length in minutes", workout.warmup_length)?
+      }
+      Edit::Link => workout.link = ask_or_clear("Link", &workout.link)?,
+      Edit::Tags => {
+        workout.tags = split(&ask_or_clear(
+          "Tags (comma separated)",
+          &workout.tags.join(", "),
+        )?)
+      }
+      Edit::Set(n) => edit_set(&mut workout, n)?,
+      Edit::AddSet => {
+        workout.sets.push(ExerciseSet {
+          exercises: vec![],
+          exercise_type: workout.workout_type,
+        });
+        let n = workout.sets.len() - 1;
+        edit_set(&mut workout, n)?;
+      }
+      Edit::Save => {
+        if save(&edits, &workout, original.as_deref())? {
+          original = Some(workout.title.clone());
+          last_saved = workout.clone();
+          saved = true;
+        }
+      }
+    }
+  }
+}
+
+/// Save the workout, showing why if it can't be. Returns whether it saved.
+fn save(
+  edits: &Edits,
+  workout: &Workout,
+  original: Option<&str>,
+) -> Result<bool> {
+  let saved = match edits.save(workout, original) {
+    Ok(()) => {
+      println!("Saved {:?}", workout.title);
+      true
+    }
+    Err(e) => {
+      println!("Couldn't save: {}", e);
+      false
+    }
+  };
+  pause()?;
+  Ok(saved)
+}
+
+fn editor_menu(workout: &Workout, unsaved: bool) -> Menu<'static, Edit> {
+  let mut menu = Menu::new(format!(
+    "Editing {}{}",
+    workout.title,
+    if unsaved { " (not saved)" } else { "" }
+  ))
+  .line(format!("Takes about {} minutes", workout.duration().as_secs() / 60))
+  .without_quit()
+  .item(format!("Title: {}", workout.title), || Edit::Title)
+  .item(format!("Day: {}", workout.day), || Edit::Day)
+  .item(format!("Type: {}", workout.workout_type), || Edit::Type)
+  .item(format!("Warmup: {} minutes", workout.warmup_length), || Edit::Warmup)
+  .item(format!("Link: {}", workout.link), || Edit::Link)
+  .item(format!("Tags: {}", workout.tags.join(", ")), || Edit::Tags);
+  for (n, set) in workout.sets.iter().enumerate() {
+    let names: Vec<&str> = set.iter().map(|e| e.name.as_str()).collect();
+    menu = menu
+      .item(format!("Set {}: {}", n + 1, names.join(", ")), move || {
+        Edit::Set(n)
+      });
+  }
+  menu.item("Add a set", || Edit::AddSet).item("Save", || Edit::Save)
+}
+
+/// Edit the set with the given (0 based) index until the user goes back.
+fn edit_set(workout: &mut Workout, mut n: usize) -> Result<()> {
+  loop {
+    let set = &workout.sets[n];
+    let mut menu = Menu::new(format!("Set {}", n + 1)).without_quit();
+    if set.exercises.is_empty() {
+      menu = menu.line("The set needs at least one exercise.");
+    }
+    for (m, exercise) in set.iter().enumerate() {
+      menu = menu.item(
+        format!("{}. {}{}", m + 1, exercise.name, exercise.details()),
+        move || SetEdit::Exercise(m),
+      );
+    }
+    menu = menu
+      .item("Add an exercise", || SetEdit::AddExercise)
+      .item(format!("Type: {}", set.exercise_type), || SetEdit::Type);
+    if n > 0 {
+      menu = menu.item("Move the set up", || SetEdit::MoveUp);
+    }
+    if n + 1 < workout.sets.len() {
+      menu = menu.item("Move the set down", || SetEdit::MoveDown);
+    }
+    menu = menu.item("Remove the set", || SetEdit::Remove);
+
+    let edit = match menu.run() {
+      Selection::Chose(edit) => edit,
+      _ => return Ok(()),
+    };
+    match edit {
+      SetEdit::Exercise(m) => edit_exercise(&mut workout.sets[n], m)?,
+      SetEdit::AddExercise => {
+        let name = ask("Exercise name", "")?;
+        if !name.is_empty() {
+          let description = ask("Description", "")?;
+          workout.sets[n].exercises.push(Exercise::new(&name, &description));
+        }
+      }
+      SetEdit::Type => {
+        if let Some(exercise_type) =
+          pick("What type of set?", &ExerciseType::VALUES)
+        {
+          workout.sets[n].exercise_type = exercise_type;
+        }
+      }
+      SetEdit::MoveUp => {
+        workout.sets.swap(n, n - 1);
+        n -= 1;
+      }
+      SetEdit::MoveDown => {
+        workout.sets.swap(n, n + 1);
+        n += 1;
+      }
+      SetEdit::Remove => {
+        workout.sets.remove(n);
+        return Ok(());
+      }
+    }
+  }
+}
+
+/// Edit the exercise with the given (0 based) index until the user goes back.
+fn edit_exercise(set: &mut ExerciseSet, mut m: usize) -> Result<()> {
+  loop {
+    let exercise = &set.exercises[m];
+    let timing = match exercise.prescription {
+      Prescription::Timed => "timed".to_string(),
+      ref prescription => prescription.to_string(),
+    };
+    let mut menu = Menu::new(&exercise.name)
+      .without_quit()
+      .item(format!("Name: {}", exercise.name), || ExerciseEdit::Name)
+      .item(format!("Description: {}", exercise.description), || {
+        ExerciseEdit::Description
+      })
+      .item(format!("Timing: {}", timing), || ExerciseEdit::Timing)
+      .item(format!("Equipment: {}", exercise.equipment.join(", ")), || {
+        ExerciseEdit::Equipment
+      });
+    if m > 0 {
+      menu = menu.item("Move the exercise up", || ExerciseEdit::MoveUp);
+    }
+    if m + 1 < set.exercises.len() {
+      menu = menu.item("Move the exercise down", || ExerciseEdit::MoveDown);
+    }
+    menu = menu.item("Remove the exercise", || ExerciseEdit::Remove);
+
+    let edit = match menu.run() {
+      Selection::Chose(edit) => edit,
+      _ => return Ok(()),
+    };
+    let exercise = &mut set.exercises[m];
+    match edit {
+      ExerciseEdit::Name => exercise.name = ask("Name", &exercise.name)?,
+      ExerciseEdit::Description => {
+        exercise.description =
+          ask_or_clear("Description", &exercise.description)?
+      }
+      ExerciseEdit::Timing => {
+        if let Some(prescription) = ask_timing(&exercise.prescription)? {
+          exercise.prescription = prescription;
+        }
+      }
+      ExerciseEdit::Equipment => {
+        exercise.equipment = split(&ask_or_clear(
+          "Equipment (comma separated)",
+          &exercise.equipment.join(", "),
+        )?)
+      }
+      ExerciseEdit::MoveUp => {
+        set.exercises.swap(m, m - 1);
+        m -= 1;
+      }
+      ExerciseEdit::MoveDown => {
+        set.exercises.swap(m, m + 1);
+        m += 1;
+      }
+      ExerciseEdit::Remove => {
+        set.exercises.remove(m);
+        return Ok(());
+      }
+    }
+  }
+}
+
+/// Ask how an exercise is done, or `None` if nothing was picked.
+fn ask_timing(current: &Prescription) -> Result<Option<Prescription>> {
+  let menu = Menu::new("How is the exercise done?")
+    .without_quit()
+    .item("Timed, like the other exercises", || 0)
+    .item("A number of reps", || 1)
+    .item("Held for a time", || 2)
+    .item("For a time on each side", || 3);
+  let (count, cap, secs) = match *current {
+    Prescription::Reps { count, cap } => (count, cap, 30),
+    Prescription::Hold { secs } | Prescription::PerSide { secs } => {
+      (10, None, secs)
+    }
+    Prescription::Timed => (10, None, 30),
+  };
+  Ok(match menu.run() {
+    Selection::Chose(0) => Some(Prescription::Timed),
+    Selection::Chose(1) => Some(Prescription::Reps {
+      count: ask_number("How many reps?", count)?,
+      cap: ask_number(
+        "At most how many seconds? (0 to wait for a key press)",
+        cap.unwrap_or(0),
+      )
+      .map(|cap| if cap == 0 { None } else { Some(cap) })?,
+    }),
+    Selection::Chose(2) => Some(Prescription::Hold {
+      secs: ask_number("Hold for how many seconds?", secs)?,
+    }),
+    Selection::Chose(_) => Some(Prescription::PerSide {
+      secs: ask_number("How many seconds on each side?", secs)?,
+    }),
+    _ => None,
+  })
+}
+
+/// Ask for some text, keeping the current value if Enter is pressed.
+fn ask(question: &str, current: &str) -> Result<String> {
+  print!("{}", clear_screen());
+  let answer = if current.is_empty() {
+    prompt(&format!("{}: ", question))?
+  } else {
+    prompt(&format!("{} [{}]: ", question, current))?
+  };
+  Ok(if answer.is_empty() { current.to_string() } else { answer })
+}
+
+/// Ask for some text that can be left empty. `-` clears it.
+fn ask_or_clear(question: &str, current: &str) -> Result<String> {
+  let answer = ask(&format!("{} (- to clear)", question), current)?;
+  Ok(if answer == "-" { String::new() } else { answer })
+}
+
+/// Ask for a number until one is given, keeping the current value if Enter
+/// is pressed.
+fn ask_number<T: FromStr + Display>(question: &str, current: T) -> Result<T> {
+  loop {
+    let answer = ask(question, &current.to_string())?;
+    if let Ok(number) = answer.parse() {
+      return Ok(number);
+    }
+  }
+}
+
+/// Choose one of the values from a menu.
+fn pick<T: Copy + Display>(heading: &str, values: &[T]) -> Option<T> {
+  let mut menu = Menu::new(heading).without_quit();
+  for value in values {
+    menu = menu.item(value.to_string(), move || *value);
+  }
+  match menu.run() {
+    Selection::Chose(value) => Some(value),
+    _ => None,
+  }
+}
+
+/// Split a comma separated list, leaving out empty entries.
+fn split(list: &str) -> Vec<String> {
+  list
+    .split(',')
+    .map(|e| e.trim().to_string())
+    .filter(|e| !e.is_empty())
+    .collect()
+}
@@ -0,0 +1,544 @@
+use crate::lib::{
+  enums::{DayOfWeek, ExerciseType},
+  events::Target,
+  export::Format,
+  history::transfer,
+  workout::{filter::Filter, importer::Conflict},
+};
+use anyhow::{bail, Result};
+use chrono::{NaiveDate, NaiveTime};
+use std::{fmt::Display, path::PathBuf};
+
+pub const USAGE: &str = "\
+Usage:
+  workout                  Show the workout menu
+  workout run <title> [--from-set N] [--events <file|fd:N|socket>]
+                           Run a workout, optionally starting from set N.
+                           --events writes what happens as lines of JSON
+  workout search <query>   Search workout titles, exercises and descriptions
+  workout list [filters]   List the workouts matching every filter given:
+    --day <day>              done on this day of the week
+    --type <type>            of this workout type, e.g. upper or lower
+    --min <minutes>          taking at least this long
+    --max <minutes>          taking at most this long
+    --equipment <a,b>        needing only this equipment (\"\" for none)
+    --tag <tag>              tagged with this
+    --not-done-in <days>     not done in this many days
+    --title <text>           with this text in the title
+    --any                    match any of the filters instead of all of them
+  workout export <title> [--format md|html] [--output <file>]
+  workout export --all [--format md|html] [--output <file>]
+                           Export a workout, or a booklet of every workout,
+                           to Markdown (the default) or printable HTML
+  workout calendar export [--time HH:MM] [--output <file>]
+                           Write a weekly calendar of the workouts on their days
+  workout history export [--format csv|json] [--since YYYY-MM-DD]
+                         [--intervals] [--output <file>]
+                           Export the sessions done so far, one row per
+                           session or per interval with --intervals
+  workout history tcx [--since YYYY-MM-DD] [--output <dir>]
+                           Write the last session, or every session since the
+                           date, as TCX files for a training log
+  workout history import <file.json>
+                           Add sessions exported as JSON on another machine
+  workout import [--on-conflict overwrite|rename|skip] [--convert]
+                 [<file>...]
+                           Import workouts from the import directory or the
+                           files given, or with --convert rewrite old import
+                           files in the new form instead. Without
+                           --on-conflict you're asked when a title is taken
+  workout edit [--external] <title>
+                           Edit a workout, or create it if there's no workout
+                           with that title. --external opens it in $EDITOR
+  workout edit --undo      Undo the last save from the editor
+  workout duplicate <title> --as <new title> [--edit]
+                           Save a copy of a workout, and with --edit open the
+                           copy in the editor
+  workout rename <title> --to <new title>
+                           Give a workout a new title
+  workout delete <title>   Move a workout to the trash
+  workout trash            List the workouts in the trash
+  workout restore <title>  Move a workout back out of the trash
+  workout upgrade          Save workout files written by an older version in
+                           the current structure
+  workout schema [--events]
+                           Print the JSON Schema for workout files, or for
+                           the lines written by run --events
+  workout help             Show this message";
+
+/// What the program was asked to do on the command line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+  /// Show the interactive menu.
+  Menu,
+  Search(String),
+  /// List the workouts matching the filter.
+  List(Filter),
+  /// Write the weekly plan as an iCalendar file.
+  CalendarExport {
+    time: Option<NaiveTime>,
+    output: Option<PathBuf>,
+  },
+  /// Export the history, printing it when there's no output file.
+  HistoryExport {
+    format: transfer::Format,
+    since: Option<NaiveDate>,
+    intervals: bool,
+    output: Option<PathBuf>,
+  },
+  /// Write the last session, or every session since a day, as TCX files to
+  /// a directory.
+  HistoryTcx {
+    since: Option<NaiveDate>,
+    output: Option<PathBuf>,
+  },
+  /// Merge sessions exported from another machine into the history.
+  HistoryImport(PathBuf),
+  /// Export a workout, or every workout when there's no title.
+  Export {
+    title: Option<String>,
+    format: Format,
+    output: Option<PathBuf>,
+  },
+  /// Run a workout straight away, optionally from a (1 based) set.
+  Run {
+    title: String,
+    from_set: Option<usize>,
+    /// Where to write the runner's events, if anywhere.
+    events: Option<Target>,
+  },
+  /// Import workout files, or convert old import files to the new form.
+  /// Without any paths the import directory is used.
+  Import {
+    convert: bool,
+    /// What to do when a title is taken, or `None` to ask each time.
+    on_conflict: Option<Conflict>,
+    paths: Vec<PathBuf>,
+  },
+  /// Edit the workout with this title, creating it if it doesn't exist,
+  /// either in the menus or in `$EDITOR`.
+  Edit {
+    title: String,
+    external: bool,
+  },
+  /// Undo the last save from the editor.
+  Undo,
+  /// Copy a workout to a new title, optionally editing the copy after.
+  Duplicate {
+    title: String,
+    new_title: String,
+    edit: bool,
+  },
+  Rename {
+    title: String,
+    new_title: String,
+  },
+  /// Move a workout to the trash.
+  Delete(String),
+  /// List the workouts in the trash.
+  Trash,
+  /// Move a workout back out of the trash.
+  Restore(String),
+  /// Save workout files from an older version in the current structure.
+  Upgrade,
+  /// Print the JSON Schema for workout files, or for the event stream.
+  Schema {
+    events: bool,
+  },
+  Help,
+}
+
+impl Command {
+  /// Parse the command line arguments, not including the program name.
+  pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+    let mut args = args.into_iter();
+    let command = match args.next() {
+      Some(command) => command,
+      None => return Ok(Command::Menu),
+    };
+
+    match command.as_str() {
+      "search" => {
+        let query: Vec<String> = args.collect();
+        if query.is_empty() {
+          bail!("search needs a query\n\n{}", USAGE);
+        }
+        Ok(Command::Search(query.join(" ")))
+      }
+      "run" => {
+        let mut title = vec![];
+        let mut from_set = None;
+        let mut events = None;
+        while let Some(arg) = args.next() {
+          match arg.as_str() {
+            "--from-set" => from_set = Some(number(&arg, args.next())?),
+            "--events" => events = Some(text(&arg, args.next())?.parse()?),
+            _ => title.push(arg),
+          }
+        }
+        if title.is_empty() {
+          bail!("run needs a workout title\n\n{}", USAGE);
+        }
+        Ok(Command::Run { title: title.join(" "), from_set, events })
+      }
+      "list" => {
+        let mut filters = vec![];
+        let mut min = None;
+        let mut max = None;
+        let mut any = false;
+        while let Some(arg) = args.next() {
+          let filter = match arg.as_str() {
+            "--day" => {
+              Filter::Day(one_of(&arg, args.next(), &DayOfWeek::VALUES)?)
+            }
+            "--type" => {
+              Filter::Type(one_of(&arg, args.next(), &ExerciseType::VALUES)?)
+            }
+            "--min" => {
+              min = Some(number(&arg, args.next())? as u64);
+              continue;
+            }
+            "--max" => {
+              max = Some(number(&arg, args.next())? as u64);
+              continue;
+            }
+            "--equipment" => Filter::Equipment(
+              text(&arg, args.next())?
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            ),
+            "--tag" => Filter::Tag(text(&arg, args.next())?),
+            "--not-done-in" => {
+              Filter::NotDoneIn(number(&arg, args.next())? as i64)
+            }
+            "--title" => Filter::Title(text(&arg, args.next())?),
+            "--any" => {
+              any = true;
+              continue;
+            }
+            _ => bail!("Unknown filter {:?}\n\n{}", arg, USAGE),
+          };
+          filters.push(filter);
+        }
+        if min.is_some() || max.is_some() {
+          filters.push(Filter::Duration { min, max });
+        }
+        Ok(Command::List(if any {
+          Filter::Any(filters)
+        } else {
+          Filter::All(filters)
+        }))
+      }
+      "export" => {
+        let mut title = vec![];
+        let mut all = false;
+        let mut format = Format::Markdown;
+        let mut output = None;
+        while let Some(arg) = args.next() {
+          match arg.as_str() {
+            "--all" => all = true,
+            "--format" => match text(&arg, args.next())?.parse() {
+              Ok(f) => format = f,
+              Err(_) => bail!("--format needs md or html"),
+            },
+            "--output" => {
+              output = Some(PathBuf::from(text(&arg, args.next())?))
+            }
+            _ => title.push(arg),
+          }
+        }
+        let title = match (all, title.is_empty()) {
+          (true, true) => None,
+          (false, false) => Some(title.join(" ")),
+          (true, false) => bail!("export takes a title or --all, not both"),
+          (false, true) => {
+            bail!("export needs a workout title or --all\n\n{}", USAGE)
+          }
+        };
+        Ok(Command::Export { title, format, output })
+      }
+      "calendar" => match args.next().as_deref() {
+        Some("export") => {
+          let mut time = None;
+          let mut output = None;
+          while let Some(arg) = args.next() {
+            match arg.as_str() {
+              "--time" => {
+                match NaiveTime::parse_from_str(
+                  &text(&arg, args.next())?,
+                  "%H:%M",
+                ) {
+                  Ok(t) => time = Some(t),
+                  Err(_) => bail!("--time needs a time like 18:30"),
+                }
+              }
+              "--output" => {
+                output = Some(PathBuf::from(text(&arg, args.next())?))
+              }
+              _ => bail!("Unknown option {:?}\n\n{}", arg, USAGE),
+            }
+          }
+          Ok(Command::CalendarExport { time, output })
+        }
+        _ => bail!("calendar needs export\n\n{}", USAGE),
+      },
+      "history" => match args.next().as_deref() {
+        Some("export") => {
+          let mut format = transfer::Format::Csv;
+          let mut since = None;
+          let mut intervals = false;
+          let mut output = None;
+          while let Some(arg) = args.next() {
+            match arg.as_str() {
+              "--format" => match text(&arg, args.next())?.parse() {
+                Ok(f) => format = f,
+                Err(_) => bail!("--format needs csv or json"),
+              },
+              "--since" => since = Some(date(&arg, args.next())?),
+              "--intervals" => intervals = true,
+              "--output" => {
+                output = Some(PathBuf::from(text(&arg, args.next())?))
+              }
+              _ => bail!("Unknown option {:?}\n\n{}", arg, USAGE),
+            }
+          }
+          if intervals && format == transfer::Format::Json {
+            bail!("--intervals only applies to csv - json has everything");
+          }
+          Ok(Command::HistoryExport { format, since, intervals, output })
+        }
+        Some("tcx") => {
+          let mut since = None;
+          let mut output = None;
+          while let Some(arg) = args.next() {
+            match arg.as_str() {
+              "--since" => since = Some(date(&arg, args.next())?),
+              "--output" => {
+                output = Some(PathBuf::from(text(&arg, args.next())?))
+              }
+              _ => bail!("Unknown option {:?}\n\n{}", arg, USAGE),
+            }
+          }
+          Ok(Command::HistoryTcx { since, output })
+        }
+        Some("import") => match args.next() {
+          Some(path) => Ok(Command::HistoryImport(PathBuf::from(path))),
+          None => bail!("history import needs a file\n\n{}", USAGE),
+        },
+        _ => bail!("history needs export, tcx or import\n\n{}", USAGE),
+      },
+      "import" => {
+        let mut convert = false;
+        let mut on_conflict = None;
+        let mut paths = vec![];
+        while let Some(arg) = args.next() {
+          match arg.as_str() {
+            "--convert" => convert = true,
+            "--on-conflict" => match text(&arg, args.next())?.parse() {
+              Ok(c) => on_conflict = Some(c),
+              Err(_) => bail!("--on-conflict needs overwrite, rename or skip"),
+            },
+            _ => paths.push(PathBuf::from(arg)),
+          }
+        }
+        Ok(Command::Import { convert, on_conflict, paths })
+      }
+      "edit" => {
+        let mut args: Vec<String> = args.collect();
+        let external = args.first().map(String::as_str) == Some("--external");
+        if external {
+          args.remove(0);
+        }
+        match args.as_slice() {
+          [] => bail!("edit needs a workout title\n\n{}", USAGE),
+          [undo] if undo == "--undo" && !external => Ok(Command::Undo),
+          _ => Ok(Command::Edit { title: args.join(" "), external }),
+        }
+      }
+      "duplicate" => {
+        let mut args: Vec<String> = args.collect();
+        let edit = args.iter().any(|a| a == "--edit");
+        args.retain(|a| a != "--edit");
+        let (title, new_title) = titles(&command, &args, "--as")?;
+        Ok(Command::Duplicate { title, new_title, edit })
+      }
+      "rename" => {
+        let args: Vec<String> = args.collect();
+        let (title, new_title) = titles(&command, &args, "--to")?;
+        Ok(Command::Rename { title, new_title })
+      }
+      "delete" | "restore" => {
+        let title: Vec<String> = args.collect();
+        if title.is_empty() {
+          bail!("{} needs a workout title\n\n{}", command, USAGE);
+        }
+        Ok(match command.as_str() {
+          "delete" => Command::Delete(title.join(" ")),
+          _ => Command::Restore(title.join(" ")),
+        })
+      }
+      "trash" => Ok(Command::Trash),
+      "upgrade" => Ok(Command::Upgrade),
+      "schema" => match args.next().as_deref() {
+        None => Ok(Command::Schema { events: false }),
+        Some("--events") => Ok(Command::Schema { events: true }),
+        Some(arg) => bail!("Unknown option {:?}\n\n{}", arg, USAGE),
+      },
+      "help" | "-h" | "--help" => Ok(Command::Help),
+      _ => bail!("Unknown command {:?}\n\n{}", command, USAGE),
+    }
+  }
+}
+
+/// The titles either side of a flag, such as `Old title --to New title`.
+fn titles(
+  command: &str,
+  args: &[String],
+  flag: &str,
+) -> Result<(String, String)> {
+  match args.iter().position(|a| a == flag) {
+    Some(at) if at > 0 && at + 1 < args.len() => {
+      Ok((args[..at].join(" "), args[at + 1..].join(" ")))
+    }
+    _ => bail!("{} needs <title> {} <new title>\n\n{}", command, flag, USAGE),
+  }
+}
+
+/// Parse the value given for a numeric flag.
+fn number(flag: &str, value: Option<String>) -> Result<usize> {
+  match value.map(|v| v.parse()) {
+    Some(Ok(n)) => Ok(n),
+    _ => bail!("{} needs a number\n\n{}", flag, USAGE),
+  }
+}
+
+/// The value given for a flag.
+fn text(flag: &str, value: Option<String>) -> Result<String> {
+  match value {
+    Some(value) => Ok(value),
+    None => bail!("{} needs a value\n\n{}", flag, USAGE),
+  }
+}
+
+/// The date given for a flag.
+fn date(flag: &str, value: Option<String>) -> Result<NaiveDate> {
+  match NaiveDate::parse_from_str(&text(flag, value)?, "%Y-%m-%d") {
+    Ok(day) => Ok(day),
+    Err(_) => bail!("{} needs a date like 2021-10-01", flag),
+  }
+}
+
+/// Pick the value whose name starts with the one given for a flag, ignoring
+/// case, so `--type upper` finds Upper Body & Abs.
+fn one_of<T: Display + Copy>(
+  flag: &str,
+  value: Option<String>,
+  values: &[T],
+) -> Result<T> {
+  let value = text(flag, value)?.to_lowercase();
+  let names: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+  let found = values.iter().find(|v| {
+    !value.is_empty() && v.to_string().to_lowercase().starts_with(&value)
+  });
+  match found {
+    Some(found) => Ok(*found),
+    None => bail!("{} needs one of: {}", flag, names.join(", ")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str]) -> Result<Command> {
+    Command::parse(args.iter().map(|a| a.to_string()))
+  }
+
+  #[test]
+  fn it_parses_commands() {
+    assert_eq!(parse(&[]).unwrap(), Command::Menu);
+    assert_eq!(parse(&["upgrade"]).unwrap(), Command::Upgrade);
+    assert_eq!(
+      parse(&["search", "diamond", "push"]).unwrap(),
+      Command::Search("diamond push".to_string())
+    );
+    assert!(parse(&["search"]).is_err());
+    assert_eq!(
+      parse(&["run", "Workout", "1.1", "--from-set", "2"]).unwrap(),
+      Command::Run {
+        title: "Workout 1.1".to_string(),
+        from_set: Some(2),
+        events: None
+      }
+    );
+    assert_eq!(
+      parse(&["run", "Legs", "--events", "fd:3"]).unwrap(),
+      Command::Run {
+        title: "Legs".to_string(),
+        from_set: None,
+        events: Some(Target::Fd(3))
+      }
+    );
+    assert!(parse(&["run", "Workout", "--from-set", "two"]).is_err());
+    assert!(parse(&["dance"]).is_err());
+    assert_eq!(
+      parse(&["list", "--type", "upper", "--day", "tuesday", "--max", "40"])
+        .unwrap(),
+      Command::List(Filter::All(vec![
+        Filter::Type(ExerciseType::UpperBodyAbs),
+        Filter::Day(DayOfWeek::Tuesday),
+        Filter::Duration { min: None, max: Some(40) },
+      ]))
+    );
+    assert!(parse(&["list", "--day", "someday"]).is_err());
+    assert_eq!(
+      parse(&["export", "--all", "--format", "html"]).unwrap(),
+      Command::Export { title: None, format: Format::Html, output: None }
+    );
+    assert!(parse(&["export"]).is_err());
+    assert_eq!(
+      parse(&["history", "export", "--since", "2021-10-01", "--intervals"])
+        .unwrap(),
+      Command::HistoryExport {
+        format: transfer::Format::Csv,
+        since: NaiveDate::from_ymd_opt(2021, 10, 1),
+        intervals: true,
+        output: None,
+      }
+    );
+    assert_eq!(
+      parse(&["import", "--on-conflict", "rename", "a.yml"]).unwrap(),
+      Command::Import {
+        convert: false,
+        on_conflict: Some(Conflict::Rename),
+        paths: vec![PathBuf::from("a.yml")],
+      }
+    );
+    assert!(parse(&["import", "--on-conflict", "merge"]).is_err());
+    assert_eq!(
+      parse(&["edit", "Workout", "1.1"]).unwrap(),
+      Command::Edit { title: "Workout 1.1".to_string(), external: false }
+    );
+    assert_eq!(
+      parse(&["edit", "--external", "Workout"]).unwrap(),
+      Command::Edit { title: "Workout".to_string(), external: true }
+    );
+    assert_eq!(parse(&["edit", "--undo"]).unwrap(), Command::Undo);
+    assert_eq!(
+      parse(&["duplicate", "Leg", "day", "--as", "Leg", "day", "2", "--edit"])
+        .unwrap(),
+      Command::Duplicate {
+        title: "Leg day".to_string(),
+        new_title: "Leg day 2".to_string(),
+        edit: true,
+      }
+    );
+    assert!(parse(&["rename", "Leg", "day", "--to"]).is_err());
+    assert_eq!(
+      parse(&["delete", "Leg", "day"]).unwrap(),
+      Command::Delete("Leg day".to_string())
+    );
+  }
+}
@@ -0,0 +1,188 @@
+use crate::lib::history::IntervalKind;
+use serde::Serialize;
+use std::{
+  fmt,
+  fmt::Formatter,
+  str::FromStr,
+  sync::{
+    mpsc::{channel, Receiver, Sender},
+    Mutex, OnceLock,
+  },
+};
+use termion::event::Key;
+
+/// Something the runner can be told to do while a workout is running, either
+/// by a key press or from elsewhere such as the HTTP server.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Control {
+  /// Pause the timer, or carry on if it's paused.
+  Pause,
+  Next,
+  Previous,
+  /// Go back to the first screen.
+  First,
+  /// Skip to the cooldown.
+  Last,
+  /// Finish a set of reps that has no time limit.
+  Done,
+  /// Stop the workout, keeping the checkpoint so it can be resumed.
+  Quit,
+}
+
+const PAUSE: &str = "pause";
+const NEXT: &str = "next";
+const PREVIOUS: &str = "previous";
+const FIRST: &str = "first";
+const LAST: &str = "last";
+const DONE: &str = "done";
+const QUIT: &str = "quit";
+
+impl Control {
+  pub const VALUES: [Self; 7] = [
+    Self::Pause,
+    Self::Next,
+    Self::Previous,
+    Self::First,
+    Self::Last,
+    Self::Done,
+    Self::Quit,
+  ];
+
+  /// The control a key press stands for, if any.
+  pub fn from_key(key: Key) -> Option<Self> {
+    match key {
+      Key::Char('q') => Some(Control::Quit),
+      Key::Char('p') => Some(Control::Pause),
+      Key::Up | Key::Left => Some(Control::Previous),
+      Key::Down | Key::Right => Some(Control::Next),
+      Key::Home => Some(Control::First),
+      Key::End => Some(Control::Last),
+      Key::Char(' ') | Key::Char('\n') => Some(Control::Done),
+      _ => None,
+    }
+  }
+}
+
+impl FromStr for Control {
+  type Err = ();
+
+  fn from_str(str: &str) -> Result<Self, ()> {
+    Control::VALUES.iter().find(|c| c.to_string() == str).copied().ok_or(())
+  }
+}
+
+impl fmt::Display for Control {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Control::Pause => PAUSE,
+        Control::Next => NEXT,
+        Control::Previous => PREVIOUS,
+        Control::First => FIRST,
+        Control::Last => LAST,
+        Control::Done => DONE,
+        Control::Quit => QUIT,
+      }
+    )
+  }
+}
+
+/// What the runner is showing, kept up to date every second so it can be
+/// shown somewhere other than the terminal.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Live {
+  pub title: String,
+  /// The (0 based) index of the current screen.
+  pub screen: usize,
+  pub screens: usize,
+  /// The heading of the screen, such as `SET 2` or `REST!`.
+  pub heading: String,
+  /// Whether the screen is the warmup, an exercise, a rest or the cooldown.
+  pub kind: IntervalKind,
+  /// The name of the exercise, or warmup, rest or cooldown.
+  pub name: String,
+  /// Seconds left on the screen, or `None` if it waits for a key press.
+  pub remaining_secs: Option<u64>,
+  /// Seconds spent on the screen so far.
+  pub elapsed_secs: u64,
+  /// Seconds left in the whole workout.
+  pub total_remaining_secs: u64,
+  /// Seconds the whole workout should take.
+  pub total_secs: u64,
+  /// The name of what comes after this screen, if anything.
+  pub next: Option<String>,
+  pub paused: bool,
+}
+
+/// The way into the runner from outside the terminal: the state of the
+/// workout being run, if there is one, and controls to send it.
+pub struct Remote {
+  live: Mutex<Option<Live>>,
+  sender: Sender<Control>,
+  controls: Mutex<Receiver<Control>>,
+}
+
+static REMOTE: OnceLock<Remote> = OnceLock::new();
+
+impl Default for Remote {
+  fn default() -> Self {
+    let (sender, controls) = channel();
+    Remote { live: Mutex::new(None), sender, controls: Mutex::new(controls) }
+  }
+}
+
+impl Remote {
+  /// Set up the remote for the rest of the program, returning it.
+  pub fn install() -> &'static Remote {
+    REMOTE.get_or_init(Remote::default)
+  }
+
+  /// The remote, if it has been set up.
+  pub fn get() -> Option<&'static Remote> {
+    REMOTE.get()
+  }
+
+  /// Send a control to the runner from another thread.
+  pub fn send(&self, control: Control) {
+    // nothing is lost if the runner has gone.
+    let _ = self.sender.send(control);
+  }
+
+  /// The next control sent, if there is one.
+  pub fn try_recv(&self) -> Option<Control> {
+    self.controls.lock().ok()?.try_recv().ok()
+  }
+
+  /// Throw away controls sent while nothing was running.
+  pub fn drain(&self) {
+    while self.try_recv().is_some() {}
+  }
+
+  /// What the runner is showing, or `None` if nothing is running.
+  pub fn live(&self) -> Option<Live> {
+    self.live.lock().ok()?.clone()
+  }
+
+  /// Show what the runner is doing, or `None` once it stops.
+  pub fn set(&self, live: Option<Live>) {
+    if let Ok(mut current) = self.live.lock() {
+      *current = live;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_names_every_control() {
+    for control in Control::VALUES.iter() {
+      assert_eq!(control.to_string().parse(), Ok(*control));
+    }
+    assert_eq!(Control::from_key(Key::Right), Some(Control::Next));
+    assert_eq!(Control::from_key(Key::Char('x')), None);
+  }
+}
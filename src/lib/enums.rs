@@ -1,3 +1,4 @@
+use chrono::{Datelike, Local, Weekday};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Formatter;
@@ -41,7 +42,7 @@ impl FromStr for ExerciseType {
   }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialOrd, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialOrd, PartialEq, Copy, Clone)]
 pub enum DayOfWeek {
   Monday,
   Tuesday,
@@ -63,6 +64,24 @@ const SUNDAY: &str = "Sunday";
 impl DayOfWeek {
   pub const VALUES: [Self; 7] =
     [Monday, Tuesday, Wednesday, Thursday, Friday, Saturday, Sunday];
+
+  pub fn today() -> Self {
+    Self::from(Local::now().weekday())
+  }
+}
+
+impl From<Weekday> for DayOfWeek {
+  fn from(day: Weekday) -> Self {
+    match day {
+      Weekday::Mon => Monday,
+      Weekday::Tue => Tuesday,
+      Weekday::Wed => Wednesday,
+      Weekday::Thu => Thursday,
+      Weekday::Fri => Friday,
+      Weekday::Sat => Saturday,
+      Weekday::Sun => Sunday,
+    }
+  }
 }
 impl FromStr for DayOfWeek {
   type Err = ();
@@ -98,8 +117,3 @@ impl fmt::Display for DayOfWeek {
     )
   }
 }
-
-pub enum Filter {
-  DayOfWeek(DayOfWeek),
-  WorkoutType(ExerciseType),
-}
@@ -0,0 +1,267 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::{
+  fs::{File, OpenOptions},
+  io::Write,
+  os::unix::{fs::FileTypeExt, io::FromRawFd, net::UnixStream},
+  path::PathBuf,
+  str::FromStr,
+  sync::{
+    mpsc::{channel, Sender},
+    OnceLock,
+  },
+  thread,
+  time::Duration,
+};
+
+/// The JSON Schema for a line of the event stream.
+pub const SCHEMA: &str = include_str!("../../schema/events.schema.json");
+
+/// Something that happened while a workout was running. Each is written as
+/// a line of JSON with the name of the event in `event`, see
+/// `schema/events.schema.json`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+  /// The workout started, or was resumed part way through.
+  SessionStart {
+    title: String,
+    screens: usize,
+    total_secs: u64,
+    /// The (0 based) screen it starts on.
+    screen: usize,
+    /// Seconds already done when it was resumed, otherwise 0.
+    elapsed_secs: u64,
+  },
+  /// A screen is shown, whether the last one ran out or was skipped.
+  ScreenStart {
+    screen: usize,
+    /// Such as `warm_up`, `exercise`, `reps` or `rest`.
+    screen_type: &'static str,
+    /// The heading shown above the screen, such as `SET 2` or `REST!`.
+    heading: String,
+    /// The name of the exercise, or warmup, rest or cooldown.
+    name: String,
+    /// How long the screen lasts, or `None` if it waits to be done.
+    duration_secs: Option<u64>,
+  },
+  /// A second went by while the timer was running.
+  Tick {
+    screen: usize,
+    elapsed_secs: u64,
+    remaining_secs: Option<u64>,
+    total_remaining_secs: u64,
+  },
+  /// The screen is about to end, as the warning sound plays.
+  Countdown {
+    screen: usize,
+    remaining_secs: u64,
+  },
+  Pause {
+    screen: usize,
+  },
+  Resume {
+    screen: usize,
+  },
+  /// A screen was left early to go to another one.
+  Skip {
+    from: usize,
+    to: usize,
+  },
+  /// The workout was stopped and can be resumed later.
+  Quit {
+    screen: usize,
+    elapsed_secs: u64,
+  },
+  /// The cooldown ran out.
+  Finish {
+    elapsed_secs: u64,
+  },
+}
+
+/// A line in the stream: the event and when it happened.
+#[derive(Serialize)]
+struct Line<'a> {
+  time: DateTime<Local>,
+  #[serde(flatten)]
+  event: &'a Event,
+}
+
+/// Where the events are written.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Target {
+  /// A file descriptor opened by whatever started the workout, `fd:N`.
+  Fd(i32),
+  /// A file that events are added to, or a unix socket to connect to.
+  Path(PathBuf),
+}
+
+impl FromStr for Target {
+  type Err = anyhow::Error;
+
+  fn from_str(str: &str) -> Result<Self> {
+    match str.strip_prefix("fd:") {
+      Some(fd) => match fd.parse() {
+        Ok(fd) => Ok(Target::Fd(fd)),
+        Err(_) => bail!("{:?} needs a file descriptor number, like fd:3", str),
+      },
+      None if str.is_empty() => bail!("--events needs a file, fd:N or socket"),
+      None => Ok(Target::Path(PathBuf::from(str))),
+    }
+  }
+}
+
+impl Target {
+  fn open(&self) -> Result<Box<dyn Write + Send>> {
+    match self {
+      Target::Fd(fd) => {
+        // taking over a descriptor that isn't open would close another file.
+        if unsafe { libc::fcntl(*fd, libc::F_GETFD) } == -1 {
+          bail!("File descriptor {} isn't open", fd);
+        }
+        Ok(Box::new(unsafe { File::from_raw_fd(*fd) }))
+      }
+      Target::Path(path) => {
+        let is_socket =
+          path.metadata().map(|m| m.file_type().is_socket()).unwrap_or(false);
+        if is_socket {
+          let stream = UnixStream::connect(path)
+            .with_context(|| format!("Couldn't connect to {:?}", path))?;
+          Ok(Box::new(stream))
+        } else {
+          let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Couldn't open {:?}", path))?;
+          Ok(Box::new(file))
+        }
+      }
+    }
+  }
+}
+
+/// What the writing thread is asked to do.
+enum Message {
+  Line(String),
+  /// Say when every line before this one has been written.
+  Flush(Sender<()>),
+}
+
+/// Sends the runner's events to a [Target] from a thread of their own, so a
+/// slow reader never holds up the timer.
+pub struct Events {
+  sender: Sender<Message>,
+}
+
+static EVENTS: OnceLock<Events> = OnceLock::new();
+
+impl Events {
+  /// How long to wait for the last events to be written before giving up.
+  const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+  /// Open the target and send the events of any workout run from now on to
+  /// it.
+  pub fn install(target: &Target) -> Result<&'static Events> {
+    let mut writer = target.open()?;
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+      for message in receiver {
+        match message {
+          Message::Line(line) => {
+            // stop once the reader has gone - there's no one to tell.
+            if writer.write_all(line.as_bytes()).is_err()
+              || writer.flush().is_err()
+            {
+              return;
+            }
+          }
+          Message::Flush(done) => {
+            let _ = done.send(());
+          }
+        }
+      }
+    });
+    Ok(EVENTS.get_or_init(|| Events { sender }))
+  }
+
+  /// The event stream, if there is one.
+  pub fn get() -> Option<&'static Events> {
+    EVENTS.get()
+  }
+
+  /// Send an event, stamped with the time now.
+  pub fn send(&self, event: Event) {
+    let _ = self.sender.send(Message::Line(line(&event, Local::now())));
+  }
+
+  /// Wait a little while for the events sent so far to be written.
+  pub fn flush(&self) {
+    let (done, finished) = channel();
+    if self.sender.send(Message::Flush(done)).is_ok() {
+      let _ = finished.recv_timeout(Events::FLUSH_TIMEOUT);
+    }
+  }
+}
+
+/// An event as a line of JSON.
+fn line(event: &Event, time: DateTime<Local>) -> String {
+  format!("{}\n", serde_json::to_string(&Line { time, event }).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::Value;
+
+  #[test]
+  fn it_writes_events_in_the_schema() {
+    let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+    let events = [
+      Event::SessionStart {
+        title: "Legs".to_string(),
+        screens: 3,
+        total_secs: 600,
+        screen: 0,
+        elapsed_secs: 0,
+      },
+      Event::ScreenStart {
+        screen: 1,
+        screen_type: "reps",
+        heading: "SET 1 - 10 REPS".to_string(),
+        name: "Squats".to_string(),
+        duration_secs: None,
+      },
+      Event::Tick {
+        screen: 1,
+        elapsed_secs: 4,
+        remaining_secs: None,
+        total_remaining_secs: 500,
+      },
+      Event::Countdown { screen: 1, remaining_secs: 7 },
+      Event::Pause { screen: 1 },
+      Event::Resume { screen: 1 },
+      Event::Skip { from: 1, to: 2 },
+      Event::Quit { screen: 2, elapsed_secs: 100 },
+      Event::Finish { elapsed_secs: 600 },
+    ];
+
+    for event in events.iter() {
+      let line = line(event, Local::now());
+      assert!(line.ends_with('\n') && line.matches('\n').count() == 1);
+      let value: Value = serde_json::from_str(&line).unwrap();
+      let name = value["event"].as_str().unwrap();
+      let properties = &schema["definitions"][name]["properties"];
+      assert!(properties.is_object(), "{} isn't in the schema", name);
+      for key in value.as_object().unwrap().keys() {
+        let known =
+          !properties[key].is_null() || !schema["properties"][key].is_null();
+        assert!(known, "{}.{} isn't in the schema", name, key);
+      }
+    }
+
+    assert_eq!("fd:3".parse::<Target>().unwrap(), Target::Fd(3));
+    assert!("fd:three".parse::<Target>().is_err());
+  }
+}
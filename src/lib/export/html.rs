@@ -0,0 +1,132 @@
+use crate::lib::{
+  timer::Timer,
+  workout::{preview, Workout},
+};
+
+/// The styles for the page. On paper every workout starts a new sheet and
+/// the contents of a booklet get a sheet of their own.
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 50em; margin: 2em auto; }
+h1 { border-bottom: 2px solid #333; }
+dt { font-weight: bold; float: left; clear: left; width: 6em; }
+dd { margin-left: 7em; }
+ol li { margin-bottom: 0.5em; }
+.details { color: #555; }
+.description { display: block; font-size: 0.9em; }
+@media print {
+  body { margin: 0; max-width: none; font-size: 11pt; }
+  a { color: inherit; text-decoration: none; }
+  .page { break-after: page; page-break-after: always; }
+  .page:last-child { break-after: auto; page-break-after: auto; }
+  .set { break-inside: avoid; page-break-inside: avoid; }
+}
+";
+
+/// Render the workouts as a self-contained HTML page. A booklet of several
+/// workouts starts with a list of contents linking to each workout.
+pub fn render(workouts: &[&Workout]) -> String {
+  let title = match workouts {
+    [workout] => workout.title.as_str(),
+    _ => "Workouts",
+  };
+
+  let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n");
+  out.push_str("<meta charset=\"utf-8\">\n");
+  out.push_str(&format!("<title>{}</title>\n", escape(title)));
+  out.push_str(&format!("<style>{}</style>\n", STYLE));
+  out.push_str("</head>\n<body>\n");
+
+  if workouts.len() > 1 {
+    out.push_str("<section class=\"page\">\n<h1>Workouts</h1>\n<ol>\n");
+    for (n, workout) in workouts.iter().enumerate() {
+      out.push_str(&format!(
+        "<li><a href=\"#workout-{}\">{}</a></li>\n",
+        n + 1,
+        escape(&workout.title)
+      ));
+    }
+    out.push_str("</ol>\n</section>\n");
+  }
+  for (n, workout) in workouts.iter().enumerate() {
+    out.push_str(&page(workout, n + 1));
+  }
+
+  out.push_str("</body>\n</html>\n");
+  out
+}
+
+/// A single workout with every set, exercise and description.
+fn page(workout: &Workout, number: usize) -> String {
+  let mut out = format!(
+    "<section class=\"page\" id=\"workout-{}\">\n<h1>{}</h1>\n<dl>\n",
+    number,
+    escape(&workout.title)
+  );
+  let mut facts = vec![
+    ("Day", escape(&workout.day.to_string())),
+    ("Type", escape(&workout.workout_type.to_string())),
+    ("Duration", workout.duration().as_secs().as_time()),
+    ("Timings", escape(&preview::timings(workout))),
+    ("Mode", escape(preview::MODE)),
+  ];
+  if !workout.link.is_empty() {
+    let link = escape(&workout.link);
+    facts.push(("Link", format!("<a href=\"{}\">{}</a>", link, link)));
+  }
+  for (name, value) in facts {
+    out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", name, value));
+  }
+  out.push_str("</dl>\n");
+
+  for (n, set) in workout.sets.iter().enumerate() {
+    out.push_str(&format!(
+      "<div class=\"set\">\n<h2>Set {}</h2>\n<ol>\n",
+      n + 1
+    ));
+    for exercise in set.iter() {
+      out.push_str(&format!(
+        "<li><strong>{}</strong><span class=\"details\">{}</span>\
+         <span class=\"description\">{}</span></li>\n",
+        escape(&exercise.name),
+        escape(&exercise.details()),
+        escape(&exercise.description)
+      ));
+    }
+    out.push_str("</ol>\n</div>\n");
+  }
+
+  out.push_str("</section>\n");
+  out
+}
+
+/// Escape the characters that mean something in HTML.
+pub fn escape(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_escapes_workouts() {
+    let workout =
+      Workout { title: "Upper <Body> & Abs".to_string(), ..Workout::default() };
+    let html = render(&[&workout]);
+    assert!(html.contains("<title>Upper &lt;Body&gt; &amp; Abs</title>"));
+    assert!(html.contains("@media print"));
+    assert_eq!(html.matches("class=\"set\"").count(), 3);
+    assert!(!html.contains("<h1>Workouts</h1>"));
+  }
+}
@@ -0,0 +1,124 @@
+use crate::lib::{enums::DayOfWeek, workout::Workout};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+
+/// The two letter day used in a weekly recurrence rule.
+fn by_day(day: DayOfWeek) -> &'static str {
+  match day {
+    DayOfWeek::Monday => "MO",
+    DayOfWeek::Tuesday => "TU",
+    DayOfWeek::Wednesday => "WE",
+    DayOfWeek::Thursday => "TH",
+    DayOfWeek::Friday => "FR",
+    DayOfWeek::Saturday => "SA",
+    DayOfWeek::Sunday => "SU",
+  }
+}
+
+/// The first date on or after `from` that falls on the day.
+fn first_date(day: DayOfWeek, from: NaiveDate) -> NaiveDate {
+  let mut date = from;
+  while DayOfWeek::from(date.weekday()) != day {
+    date += Duration::days(1);
+  }
+  date
+}
+
+/// Escape the characters that mean something in a text value.
+fn escape(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace(';', "\\;")
+    .replace(',', "\\,")
+    .replace('\n', "\\n")
+}
+
+/// A content line, folded so no line is longer than 75 bytes.
+fn line(out: &mut String, content: &str) {
+  let mut length = 0;
+  for c in content.chars() {
+    if length + c.len_utf8() > 75 {
+      out.push_str("\r\n ");
+      length = 1;
+    }
+    out.push(c);
+    length += c.len_utf8();
+  }
+  out.push_str("\r\n");
+}
+
+/// An iCalendar file with a weekly event for each workout on its day, at
+/// `time` local time, starting from the first matching day on or after
+/// `from`. `stamp` is when the file was made.
+pub fn render(
+  workouts: &[&Workout],
+  time: NaiveTime,
+  from: NaiveDate,
+  stamp: DateTime<Utc>,
+) -> String {
+  let mut out = String::new();
+  line(&mut out, "BEGIN:VCALENDAR");
+  line(&mut out, "VERSION:2.0");
+  line(&mut out, "PRODID:-//Grillbrick Studios//workout//EN");
+  line(&mut out, "CALSCALE:GREGORIAN");
+
+  for workout in workouts {
+    let start = first_date(workout.day, from).and_time(time);
+    let uid: String = workout
+      .title
+      .chars()
+      .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+      .collect();
+    let mut description = vec![];
+    for (n, set) in workout.sets.iter().enumerate() {
+      description.push(format!("Set {}", n + 1));
+      for exercise in set.iter() {
+        description.push(format!("- {}{}", exercise.name, exercise.details()));
+      }
+    }
+
+    line(&mut out, "BEGIN:VEVENT");
+    line(&mut out, &format!("UID:{}@workout", uid));
+    line(&mut out, &format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+    line(&mut out, &format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+    line(
+      &mut out,
+      &format!("DURATION:PT{}M", workout.duration().as_secs().div_ceil(60)),
+    );
+    line(&mut out, &format!("RRULE:FREQ=WEEKLY;BYDAY={}", by_day(workout.day)));
+    line(&mut out, &format!("SUMMARY:{}", escape(&workout.title)));
+    if !workout.link.is_empty() {
+      line(&mut out, &format!("URL:{}", workout.link));
+    }
+    line(&mut out, &format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+    line(&mut out, "END:VEVENT");
+  }
+
+  line(&mut out, "END:VCALENDAR");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  #[test]
+  fn it_repeats_each_workout_weekly() {
+    let workout = Workout {
+      title: "Upper, 1".to_string(),
+      day: DayOfWeek::Wednesday,
+      ..Workout::default()
+    };
+    // a Monday
+    let from = NaiveDate::from_ymd_opt(2021, 10, 4).unwrap();
+    let time = NaiveTime::from_hms_opt(18, 30, 0).unwrap();
+    let stamp = Utc.with_ymd_and_hms(2021, 10, 1, 0, 0, 0).unwrap();
+    let ics = render(&[&workout], time, from, stamp);
+
+    assert!(ics.contains("DTSTART:20211006T183000\r\n"));
+    assert!(ics.contains("RRULE:FREQ=WEEKLY;BYDAY=WE\r\n"));
+    assert!(ics.contains("SUMMARY:Upper\\, 1\r\n"));
+    assert!(ics.lines().all(|l| l.len() <= 76));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+  }
+}
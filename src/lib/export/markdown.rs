@@ -0,0 +1,74 @@
+use crate::lib::{
+  timer::Timer,
+  workout::{preview, Workout},
+};
+
+/// Render the workouts as Markdown. A booklet of several workouts starts with
+/// a list of contents and puts a rule between workouts.
+pub fn render(workouts: &[&Workout]) -> String {
+  let mut out = String::new();
+  if workouts.len() > 1 {
+    out.push_str("# Workouts\n\n");
+    for workout in workouts {
+      out.push_str(&format!("- {}\n", workout.title));
+    }
+    out.push_str("\n---\n\n");
+  }
+
+  let pages: Vec<String> = workouts.iter().map(|w| workout(w)).collect();
+  out.push_str(&pages.join("\n---\n\n"));
+  out
+}
+
+/// A single workout with every set, exercise and description.
+fn workout(workout: &Workout) -> String {
+  let mut out = format!("# {}\n\n", workout.title);
+  out.push_str(&format!("- **Day:** {}\n", workout.day));
+  out.push_str(&format!("- **Type:** {}\n", workout.workout_type));
+  out.push_str(&format!(
+    "- **Duration:** {}\n",
+    workout.duration().as_secs().as_time()
+  ));
+  out.push_str(&format!("- **Timings:** {}\n", preview::timings(workout)));
+  out.push_str(&format!("- **Mode:** {}\n", preview::MODE));
+  if !workout.link.is_empty() {
+    out.push_str(&format!("- **Link:** <{}>\n", workout.link));
+  }
+
+  for (n, set) in workout.sets.iter().enumerate() {
+    out.push_str(&format!("\n## Set {}\n\n", n + 1));
+    for (e, exercise) in set.iter().enumerate() {
+      out.push_str(&format!(
+        "{}. **{}**{}\n",
+        e + 1,
+        exercise.name,
+        exercise.details()
+      ));
+      if !exercise.description.is_empty() {
+        out.push_str(&format!("   {}\n", exercise.description));
+      }
+    }
+  }
+
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_renders_a_booklet() {
+    let a = Workout { title: "Upper 1".to_string(), ..Workout::default() };
+    let b = Workout { title: "Lower 1".to_string(), ..Workout::default() };
+
+    let single = render(&[&a]);
+    assert!(single.starts_with("# Upper 1\n"));
+    assert!(single.contains("## Set 3"));
+    assert!(single.contains("1. **Do stuff**\n   This is how"));
+
+    let booklet = render(&[&a, &b]);
+    assert!(booklet.starts_with("# Workouts\n\n- Upper 1\n- Lower 1\n"));
+    assert_eq!(booklet.matches("\n---\n").count(), 2);
+  }
+}
@@ -0,0 +1,58 @@
+pub mod html;
+pub mod ics;
+pub mod markdown;
+
+use crate::lib::workout::Workout;
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// The formats a workout can be exported to.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Format {
+  Markdown,
+  /// A self-contained page that prints one workout per sheet.
+  Html,
+}
+
+const MARKDOWN: &str = "md";
+const HTML: &str = "html";
+
+impl Format {
+  pub const VALUES: [Self; 2] = [Format::Markdown, Format::Html];
+
+  /// The file extension for the format.
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Format::Markdown => MARKDOWN,
+      Format::Html => HTML,
+    }
+  }
+
+  /// Render the workouts. More than one workout makes a booklet with a list
+  /// of contents at the front.
+  pub fn render(&self, workouts: &[&Workout]) -> String {
+    match self {
+      Format::Markdown => markdown::render(workouts),
+      Format::Html => html::render(workouts),
+    }
+  }
+}
+
+impl FromStr for Format {
+  type Err = ();
+
+  fn from_str(str: &str) -> Result<Self, ()> {
+    match str.to_lowercase().as_str() {
+      MARKDOWN | "markdown" => Ok(Format::Markdown),
+      HTML | "htm" => Ok(Format::Html),
+      _ => Err(()),
+    }
+  }
+}
+
+impl fmt::Display for Format {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.extension())
+  }
+}
@@ -0,0 +1,124 @@
+use anyhow::{bail, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::{fs::File, path::Path};
+
+/// The JSON Schema describing a workout file, for editors to autocomplete and
+/// validate against.
+pub const SCHEMA: &str = include_str!("../../schema/workout.schema.json");
+
+/// The formats workout files can be written in, told apart by extension.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+  #[default]
+  Yaml,
+  Json,
+  Toml,
+}
+
+const YAML: &str = "yml";
+const JSON: &str = "json";
+const TOML: &str = "toml";
+
+impl FileFormat {
+  pub const VALUES: [Self; 3] =
+    [FileFormat::Yaml, FileFormat::Json, FileFormat::Toml];
+
+  /// The format of a file going by its extension.
+  pub fn from_path(path: &Path) -> Result<Self> {
+    let extension = path
+      .extension()
+      .and_then(|e| e.to_str())
+      .map(|e| e.to_lowercase())
+      .unwrap_or_default();
+    match extension.as_str() {
+      YAML | "yaml" => Ok(FileFormat::Yaml),
+      JSON => Ok(FileFormat::Json),
+      TOML => Ok(FileFormat::Toml),
+      _ => bail!("{:?} isn't a .yml, .yaml, .json or .toml file", path),
+    }
+  }
+
+  /// The extension files in this format are saved with.
+  pub fn extension(&self) -> &'static str {
+    match self {
+      FileFormat::Yaml => YAML,
+      FileFormat::Json => JSON,
+      FileFormat::Toml => TOML,
+    }
+  }
+
+  /// Read a file in the format its extension says it's in.
+  pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    Ok(match Self::from_path(path)? {
+      FileFormat::Yaml => serde_yaml::from_reader(File::open(path)?)?,
+      FileFormat::Json => serde_json::from_reader(File::open(path)?)?,
+      FileFormat::Toml => toml::from_str(&std::fs::read_to_string(path)?)?,
+    })
+  }
+
+  /// Write a value to a file in this format.
+  pub fn write<T: Serialize>(&self, path: &Path, value: &T) -> Result<()> {
+    match self {
+      FileFormat::Yaml => serde_yaml::to_writer(File::create(path)?, value)?,
+      FileFormat::Json => {
+        serde_json::to_writer_pretty(File::create(path)?, value)?
+      }
+      FileFormat::Toml => {
+        // going through a toml::Value puts plain values before tables, which
+        // toml needs.
+        let value = toml::Value::try_from(value)?;
+        std::fs::write(path, toml::to_string(&value)?)?
+      }
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Display for FileFormat {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.extension())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lib::util::test_dir;
+  use crate::lib::workout::Workout;
+
+  #[test]
+  fn it_round_trips_workouts_in_every_format() {
+    let dir = test_dir("file-format");
+    let workout =
+      Workout { tags: vec!["quick".to_string()], ..Workout::default() };
+
+    for format in FileFormat::VALUES.iter() {
+      let path = dir.join(format!("workout.{}", format.extension()));
+      format.write(&path, &workout).unwrap();
+      let read: Workout = FileFormat::read(&path).unwrap();
+      assert_eq!(read.title, workout.title);
+      assert_eq!(read.tags, workout.tags);
+      assert_eq!(read.sets.len(), 3);
+    }
+    assert!(FileFormat::from_path(Path::new("workout.txt")).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn it_describes_every_workout_field_in_the_schema() {
+    let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+    let workout =
+      Workout { tags: vec!["quick".to_string()], ..Workout::default() };
+    let workout = serde_json::to_value(&workout).unwrap();
+    for field in workout.as_object().unwrap().keys() {
+      assert!(
+        schema["properties"].get(field).is_some(),
+        "{} is missing from the schema",
+        field
+      );
+    }
+  }
+}
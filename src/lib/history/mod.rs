@@ -0,0 +1,289 @@
+use crate::lib::workout::load::{Load, WeightUnit};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{from_reader, to_writer};
+use std::{fs::File, path::Path};
+use workout_paths::history_path;
+
+pub mod progression;
+pub mod tcx;
+pub mod transfer;
+
+/// A weight actually lifted for an exercise during a session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Lift {
+  pub exercise: String,
+  pub weight: f32,
+  pub unit: WeightUnit,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub reps: Option<u32>,
+}
+
+impl Lift {
+  pub fn new(exercise: &str, load: Load, reps: Option<u32>) -> Self {
+    Lift {
+      exercise: exercise.to_owned(),
+      weight: load.weight,
+      unit: load.unit,
+      reps,
+    }
+  }
+
+  pub fn load(&self) -> Load {
+    Load::new(self.weight, self.unit)
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone, Default)]
+pub enum IntervalKind {
+  WarmUp,
+  Rest,
+  #[default]
+  Exercise,
+  Cooldown,
+}
+
+/// How an interval ended.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
+pub enum Outcome {
+  /// The timer ran out or the reps were finished.
+  Completed,
+  /// The interval was left early by moving to another screen.
+  Skipped,
+  /// The workout was quit during the interval.
+  Stopped,
+}
+
+/// A single screen of a workout as it was actually done.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interval {
+  pub kind: IntervalKind,
+  pub name: String,
+  pub planned_secs: u64,
+  pub actual_secs: u64,
+  pub outcome: Outcome,
+}
+
+/// What happened while a workout was running.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RunStats {
+  /// Every screen visited, in the order they were visited.
+  #[serde(default)]
+  pub intervals: Vec<Interval>,
+  /// The number of seconds the workout ran for.
+  #[serde(default)]
+  pub elapsed_secs: u64,
+  /// The number of seconds the workout should have taken.
+  #[serde(default)]
+  pub planned_secs: u64,
+  /// The number of seconds the workout was paused for.
+  #[serde(default)]
+  pub paused_secs: u64,
+}
+
+impl RunStats {
+  fn exercises(&self) -> impl Iterator<Item = &Interval> {
+    self.intervals.iter().filter(|i| i.kind == IntervalKind::Exercise)
+  }
+
+  /// The number of seconds spent on exercises.
+  pub fn active_secs(&self) -> u64 {
+    self.exercises().map(|i| i.actual_secs).sum()
+  }
+
+  /// The number of exercise intervals that were completed.
+  pub fn completed(&self) -> usize {
+    self.exercises().filter(|i| i.outcome == Outcome::Completed).count()
+  }
+
+  /// The number of exercise intervals that were skipped.
+  pub fn skipped(&self) -> usize {
+    self.exercises().filter(|i| i.outcome == Outcome::Skipped).count()
+  }
+
+  /// The number of seconds the workout ran past its planned length.
+  pub fn overtime_secs(&self) -> u64 {
+    self.elapsed_secs.saturating_sub(self.planned_secs)
+  }
+}
+
+/// A single time a workout was done.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+  pub title: String,
+  pub started: DateTime<Local>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub lifts: Vec<Lift>,
+  #[serde(flatten)]
+  pub stats: RunStats,
+  /// How hard the workout felt from 1 to 10.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rpe: Option<u8>,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub note: String,
+}
+
+impl Session {
+  pub fn new(title: &str, started: DateTime<Local>) -> Self {
+    Session {
+      title: title.to_owned(),
+      started,
+      lifts: vec![],
+      stats: RunStats::default(),
+      rpe: None,
+      note: String::new(),
+    }
+  }
+
+  /// The file name the session is stored under in the history directory.
+  pub fn file_name(&self) -> String {
+    format!("{}.yml", self.file_stem())
+  }
+
+  /// The start time and title, for naming files about the session. The title
+  /// is part of it so that two workouts started in the same second are kept
+  /// apart.
+  pub fn file_stem(&self) -> String {
+    let title: String = self
+      .title
+      .chars()
+      .map(|c| if c.is_alphanumeric() { c } else { '-' })
+      .collect();
+    format!("{}_{}", self.started.format("%Y-%m-%d_%H-%M-%S"), title)
+  }
+
+  /// Whether this is the same time doing the same workout as `other`.
+  pub fn same_as(&self, other: &Session) -> bool {
+    self.title == other.title && self.started == other.started
+  }
+
+  /// Load a single yaml file as a session.
+  pub fn load_file(filename: &Path) -> Result<Self> {
+    let f = File::open(filename)?;
+    let result: Session = from_reader(f)?;
+    Ok(result)
+  }
+
+  /// Load every recorded session, oldest first.
+  pub fn load_all() -> Result<Vec<Self>> {
+    let paths = match std::fs::read_dir(history_path()) {
+      Ok(p) => p,
+      Err(_) => {
+        return Ok(vec![]);
+      }
+    };
+    let paths = paths
+      .map(|res| res.map(|e| e.path()))
+      .collect::<Result<Vec<_>, std::io::Error>>()?;
+    let mut sessions = vec![];
+    for path in paths {
+      match Self::load_file(&path) {
+        Ok(session) => sessions.push(session),
+        Err(e) => println!("Error loading session {:?}: {:?}", path, e),
+      }
+    }
+    sessions.sort_by_key(|s| s.started);
+
+    Ok(sessions)
+  }
+
+  /// Save the session in the history directory, replacing the file saved
+  /// for it before, such as when it was interrupted.
+  pub fn save(&self) -> Result<()> {
+    std::fs::create_dir_all(history_path())?;
+    let path = history_path().join(self.file_name());
+    if let Ok(saved) = Session::load_file(&path) {
+      if !saved.same_as(self) {
+        bail!(
+          "{:?} already holds {} started {}",
+          path,
+          saved.title,
+          saved.started
+        );
+      }
+    }
+    let f = File::create(path)?;
+    to_writer(f, &self)?;
+    Ok(())
+  }
+}
+
+/// When a workout was last started.
+pub fn last_done(sessions: &[Session], title: &str) -> Option<DateTime<Local>> {
+  sessions.iter().filter(|s| s.title == title).map(|s| s.started).max()
+}
+
+/// The names of every exercise that has a lift recorded, sorted.
+pub fn lifted_exercises(sessions: &[Session]) -> Vec<String> {
+  let mut names: Vec<String> = sessions
+    .iter()
+    .flat_map(|s| s.lifts.iter().map(|l| l.exercise.clone()))
+    .collect();
+  names.sort();
+  names.dedup();
+  names
+}
+
+/// Every lift recorded for an exercise, oldest first.
+pub fn progress<'a>(
+  sessions: &'a [Session],
+  exercise: &str,
+) -> Vec<(&'a Session, &'a Lift)> {
+  sessions
+    .iter()
+    .flat_map(|s| {
+      s.lifts.iter().filter(|l| l.exercise == exercise).map(move |l| (s, l))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn interval(
+    kind: IntervalKind,
+    actual_secs: u64,
+    outcome: Outcome,
+  ) -> Interval {
+    Interval {
+      kind,
+      name: "Squats".to_string(),
+      planned_secs: 20,
+      actual_secs,
+      outcome,
+    }
+  }
+
+  #[test]
+  fn it_counts_only_exercise_intervals() {
+    let stats = RunStats {
+      intervals: vec![
+        interval(IntervalKind::WarmUp, 300, Outcome::Completed),
+        interval(IntervalKind::Exercise, 20, Outcome::Completed),
+        interval(IntervalKind::Exercise, 5, Outcome::Skipped),
+        interval(IntervalKind::Rest, 60, Outcome::Skipped),
+        interval(IntervalKind::Exercise, 8, Outcome::Stopped),
+      ],
+      elapsed_secs: 400,
+      planned_secs: 380,
+      paused_secs: 0,
+    };
+    assert_eq!(stats.active_secs(), 33);
+    assert_eq!(stats.completed(), 1);
+    assert_eq!(stats.skipped(), 1);
+    assert_eq!(stats.overtime_secs(), 20);
+  }
+
+  #[test]
+  fn it_names_sessions_by_start_and_title() {
+    let started = Local::now();
+    let legs = Session::new("Legs 1.1", started);
+    let arms = Session::new("Arms", started);
+    assert_ne!(legs.file_name(), arms.file_name());
+    assert!(legs.file_name().ends_with("_Legs-1-1.yml"));
+    assert!(legs.same_as(&Session::new("Legs 1.1", started)));
+    assert!(!legs.same_as(&arms));
+  }
+}
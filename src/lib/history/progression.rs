@@ -0,0 +1,66 @@
+use super::Lift;
+use crate::lib::workout::load::{Load, WeightUnit};
+use serde::{Deserialize, Serialize};
+
+/// How the next load is suggested from the last one lifted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ProgressionRule {
+  /// Add a fixed amount every session.
+  Linear { kg: f32, lb: f32 },
+  /// Add a percentage of the last load, rounded to the nearest half unit.
+  Percent { percent: f32 },
+  /// Only add weight once the last session reached the target reps.
+  RepTarget { reps: u32, kg: f32, lb: f32 },
+}
+
+impl Default for ProgressionRule {
+  fn default() -> Self {
+    ProgressionRule::RepTarget { reps: 12, kg: 2.5, lb: 5.0 }
+  }
+}
+
+impl ProgressionRule {
+  /// Suggest the load for next time based on the last lift.
+  pub fn suggest(&self, last: &Lift) -> Load {
+    let load = last.load();
+    let increment = |kg: f32, lb: f32| match load.unit {
+      WeightUnit::Kg => kg,
+      WeightUnit::Lb => lb,
+    };
+    let weight = match self {
+      ProgressionRule::Linear { kg, lb } => load.weight + increment(*kg, *lb),
+      ProgressionRule::Percent { percent } => {
+        let weight = load.weight * (1.0 + percent / 100.0);
+        (weight * 2.0).round() / 2.0
+      }
+      ProgressionRule::RepTarget { reps, kg, lb } => match last.reps {
+        Some(done) if done >= *reps => load.weight + increment(*kg, *lb),
+        _ => load.weight,
+      },
+    };
+    Load::new(weight, load.unit)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lift(weight: f32, reps: u32) -> Lift {
+    Lift::new("Goblet Squats", Load::new(weight, WeightUnit::Kg), Some(reps))
+  }
+
+  #[test]
+  fn it_adds_weight_once_the_rep_target_is_reached() {
+    let rule = ProgressionRule::RepTarget { reps: 12, kg: 2.5, lb: 5.0 };
+    assert_eq!(rule.suggest(&lift(16.0, 10)).weight, 16.0);
+    assert_eq!(rule.suggest(&lift(16.0, 12)).weight, 18.5);
+  }
+
+  #[test]
+  fn it_rounds_percentage_increases_to_half_units() {
+    let rule = ProgressionRule::Percent { percent: 5.0 };
+    assert_eq!(rule.suggest(&lift(20.0, 10)).weight, 21.0);
+    assert_eq!(rule.suggest(&lift(16.0, 10)).weight, 17.0);
+  }
+}
@@ -0,0 +1,134 @@
+use super::{Interval, IntervalKind, Outcome, Session};
+use crate::lib::export::html::escape;
+use chrono::{Duration, SecondsFormat, Utc};
+
+/// A Training Center XML activity for the session, which most training logs
+/// can import. There is a lap for every exercise and rest interval, timed
+/// from when the session started and how long each interval really took.
+/// Warmups and cooldowns aren't laps but still move the clock on. A session
+/// without any exercise or rest intervals, such as one quit during the
+/// warmup, is a single lap, since an activity has to have at least one.
+///
+/// The history only keeps the total time spent paused, not when, so lap
+/// start times leave pauses out: after a pause, laps start earlier than they
+/// really did.
+pub fn render(session: &Session) -> String {
+  let time = |offset: u64| {
+    (session.started + Duration::seconds(offset as i64))
+      .with_timezone(&Utc)
+      .to_rfc3339_opts(SecondsFormat::Secs, true)
+  };
+
+  let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  out.push_str(
+    "<TrainingCenterDatabase \
+     xmlns=\"http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2\">\n",
+  );
+  out.push_str("  <Activities>\n    <Activity Sport=\"Other\">\n");
+  out.push_str(&format!("      <Id>{}</Id>\n", time(0)));
+
+  // one lap for the whole session when there are no exercises or rests,
+  // including sessions recorded before intervals were tracked.
+  let whole = [Interval {
+    kind: IntervalKind::Exercise,
+    name: session.title.clone(),
+    planned_secs: session.stats.planned_secs,
+    actual_secs: session.stats.elapsed_secs,
+    outcome: Outcome::Completed,
+  }];
+  let intervals = &session.stats.intervals;
+  let has_laps = intervals
+    .iter()
+    .any(|i| matches!(i.kind, IntervalKind::Exercise | IntervalKind::Rest));
+  let intervals = if has_laps { &intervals[..] } else { &whole[..] };
+
+  let mut offset = 0;
+  for interval in intervals.iter() {
+    let intensity = match interval.kind {
+      IntervalKind::Exercise => "Active",
+      IntervalKind::Rest => "Resting",
+      IntervalKind::WarmUp | IntervalKind::Cooldown => {
+        offset += interval.actual_secs;
+        continue;
+      }
+    };
+    out.push_str(&format!("      <Lap StartTime=\"{}\">\n", time(offset)));
+    out.push_str(&format!(
+      "        <TotalTimeSeconds>{}</TotalTimeSeconds>\n",
+      interval.actual_secs
+    ));
+    out.push_str("        <DistanceMeters>0</DistanceMeters>\n");
+    out.push_str("        <Calories>0</Calories>\n");
+    out.push_str(&format!("        <Intensity>{}</Intensity>\n", intensity));
+    out.push_str("        <TriggerMethod>Manual</TriggerMethod>\n");
+    out.push_str(&format!(
+      "        <Notes>{}</Notes>\n",
+      escape(&interval.name)
+    ));
+    out.push_str("      </Lap>\n");
+    offset += interval.actual_secs;
+  }
+
+  let mut notes = vec![format!("HIIT: {}", session.title)];
+  if let Some(rpe) = session.rpe {
+    notes.push(format!("RPE {}", rpe));
+  }
+  if !session.note.is_empty() {
+    notes.push(session.note.clone());
+  }
+  out.push_str(&format!(
+    "      <Notes>{}</Notes>\n",
+    escape(&notes.join(" - "))
+  ));
+  out.push_str("    </Activity>\n  </Activities>\n</TrainingCenterDatabase>\n");
+  out
+}
+
+/// The name of the file a session is exported to.
+pub fn file_name(session: &Session) -> String {
+  format!("{}.tcx", session.file_stem())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{Local, TimeZone};
+
+  #[test]
+  fn it_makes_a_lap_for_each_exercise_and_rest() {
+    let started = Local.with_ymd_and_hms(2021, 10, 1, 7, 0, 0).unwrap();
+    let mut session = Session::new("Upper & Abs", started);
+    for (kind, secs) in [
+      (IntervalKind::WarmUp, 300),
+      (IntervalKind::Exercise, 22),
+      (IntervalKind::Rest, 60),
+      (IntervalKind::Cooldown, 600),
+    ] {
+      session.stats.intervals.push(Interval {
+        kind,
+        name: format!("{:?}", kind),
+        planned_secs: secs,
+        actual_secs: secs,
+        outcome: Outcome::Completed,
+      });
+    }
+
+    let tcx = render(&session);
+    assert_eq!(tcx.matches("<Lap ").count(), 2);
+    let rest_start = (started + Duration::seconds(322)).with_timezone(&Utc);
+    assert!(tcx.contains(&format!(
+      "<Lap StartTime=\"{}\">",
+      rest_start.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )));
+    assert!(tcx.contains("<Intensity>Resting</Intensity>"));
+    assert!(tcx.contains("<Notes>HIIT: Upper &amp; Abs</Notes>"));
+    assert_eq!(file_name(&session), "2021-10-01_07-00-00_Upper---Abs.tcx");
+
+    // quit during the warmup.
+    session.stats.intervals.truncate(1);
+    session.stats.elapsed_secs = 120;
+    let tcx = render(&session);
+    assert_eq!(tcx.matches("<Lap ").count(), 1);
+    assert!(tcx.contains("<TotalTimeSeconds>120</TotalTimeSeconds>"));
+  }
+}
@@ -0,0 +1,201 @@
+use super::Session;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// The formats the history can be exported to. Only JSON keeps everything
+/// about a session, so only JSON can be imported again.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Format {
+  Csv,
+  Json,
+}
+
+const CSV: &str = "csv";
+const JSON: &str = "json";
+
+impl FromStr for Format {
+  type Err = ();
+
+  fn from_str(str: &str) -> Result<Self, ()> {
+    match str.to_lowercase().as_str() {
+      CSV => Ok(Format::Csv),
+      JSON => Ok(Format::Json),
+      _ => Err(()),
+    }
+  }
+}
+
+impl fmt::Display for Format {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Format::Csv => CSV,
+        Format::Json => JSON,
+      }
+    )
+  }
+}
+
+/// The sessions started on or after a day, or every session.
+pub fn since(sessions: &[Session], day: Option<NaiveDate>) -> Vec<&Session> {
+  sessions
+    .iter()
+    .filter(|s| day.is_none_or(|day| s.started.date_naive() >= day))
+    .collect()
+}
+
+/// Quote a CSV field if it needs it.
+fn field(text: &str) -> String {
+  if text.contains(&[',', '"', '\n', '\r'][..]) {
+    format!("\"{}\"", text.replace('"', "\"\""))
+  } else {
+    text.to_string()
+  }
+}
+
+fn row(fields: &[String]) -> String {
+  let fields: Vec<String> = fields.iter().map(|f| field(f)).collect();
+  format!("{}\n", fields.join(","))
+}
+
+/// One row per session, or one row per interval of every session.
+pub fn to_csv(sessions: &[&Session], intervals: bool) -> String {
+  let mut out = String::new();
+  if intervals {
+    out.push_str(
+      "started,title,interval,kind,name,planned_secs,actual_secs,outcome\n",
+    );
+    for session in sessions {
+      for (n, interval) in session.stats.intervals.iter().enumerate() {
+        out.push_str(&row(&[
+          session.started.to_rfc3339(),
+          session.title.clone(),
+          (n + 1).to_string(),
+          format!("{:?}", interval.kind),
+          interval.name.clone(),
+          interval.planned_secs.to_string(),
+          interval.actual_secs.to_string(),
+          format!("{:?}", interval.outcome),
+        ]));
+      }
+    }
+    return out;
+  }
+
+  out.push_str(
+    "started,title,elapsed_secs,active_secs,planned_secs,paused_secs,\
+     completed,skipped,overtime_secs,rpe,note,lifts\n",
+  );
+  for session in sessions {
+    let stats = &session.stats;
+    let lifts: Vec<String> = session
+      .lifts
+      .iter()
+      .map(|l| match l.reps {
+        Some(reps) => format!("{} {} x {}", l.exercise, l.load(), reps),
+        None => format!("{} {}", l.exercise, l.load()),
+      })
+      .collect();
+    out.push_str(&row(&[
+      session.started.to_rfc3339(),
+      session.title.clone(),
+      stats.elapsed_secs.to_string(),
+      stats.active_secs().to_string(),
+      stats.planned_secs.to_string(),
+      stats.paused_secs.to_string(),
+      stats.completed().to_string(),
+      stats.skipped().to_string(),
+      stats.overtime_secs().to_string(),
+      session.rpe.map(|r| r.to_string()).unwrap_or_default(),
+      session.note.clone(),
+      lifts.join("; "),
+    ]));
+  }
+  out
+}
+
+pub fn to_json(sessions: &[&Session]) -> Result<String> {
+  Ok(serde_json::to_string_pretty(sessions)?)
+}
+
+pub fn from_json(text: &str) -> Result<Vec<Session>> {
+  Ok(serde_json::from_str(text)?)
+}
+
+/// The imported sessions that aren't in the history yet. A session is the
+/// same as another when it's for the same workout and started at the same
+/// time.
+pub fn new_sessions(
+  history: &[Session],
+  imported: Vec<Session>,
+) -> Vec<Session> {
+  let mut new: Vec<Session> = vec![];
+  for session in imported {
+    let same = |s: &Session| s.same_as(&session);
+    if !history.iter().any(same) && !new.iter().any(same) {
+      new.push(session);
+    }
+  }
+  new
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lib::history::{Interval, IntervalKind, Outcome};
+  use chrono::{Local, TimeZone};
+
+  fn session(title: &str, day: u32) -> Session {
+    let mut session = Session::new(
+      title,
+      Local.with_ymd_and_hms(2021, 10, day, 7, 0, 0).unwrap(),
+    );
+    session.stats.intervals.push(Interval {
+      kind: IntervalKind::Exercise,
+      name: "Squats, slow".to_string(),
+      planned_secs: 20,
+      actual_secs: 22,
+      outcome: Outcome::Completed,
+    });
+    session.note = "Felt \"good\"".to_string();
+    session
+  }
+
+  #[test]
+  fn it_writes_csv_rows() {
+    let sessions = vec![session("Upper", 1), session("Lower", 3)];
+    let day = NaiveDate::from_ymd_opt(2021, 10, 2);
+    let recent = since(&sessions, day);
+    assert_eq!(recent.len(), 1);
+
+    let csv = to_csv(&recent, false);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains(",Lower,0,22,0,0,1,0,0,,\"Felt \"\"good\"\"\","));
+
+    let csv = to_csv(&recent, true);
+    assert!(csv
+      .lines()
+      .nth(1)
+      .unwrap()
+      .ends_with(",Lower,1,Exercise,\"Squats, slow\",20,22,Completed"));
+  }
+
+  #[test]
+  fn it_merges_without_duplicates() {
+    let history = vec![session("Upper", 1)];
+    let json = to_json(&[&history[0], &session("Lower", 3)]).unwrap();
+    let mut imported = from_json(&json).unwrap();
+    imported.push(session("Lower", 3));
+
+    let new = new_sessions(&history, imported);
+    assert_eq!(new.len(), 1);
+    assert_eq!(new[0].title, "Lower");
+    assert_eq!(new[0].stats.intervals[0].actual_secs, 22);
+  }
+}
@@ -0,0 +1,233 @@
+use crate::lib::{events::Event, settings::Settings};
+use anyhow::{bail, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+  fs::{File, OpenOptions},
+  io::Write,
+  os::unix::process::CommandExt,
+  path::Path,
+  process::{Command, Stdio},
+  thread,
+  time::{Duration, Instant},
+};
+use workout_paths::state_path;
+
+/// Where the output of the commands goes, with a line for each that failed.
+const LOG_FILE: &str = "hooks.log";
+
+/// How often a running command is checked on.
+const POLL: Duration = Duration::from_millis(100);
+
+/// Shell commands from the settings that are run when things happen in a
+/// workout. They run in the background, so they never hold up the timer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Hooks {
+  pub workout_start: Option<String>,
+  /// Run as each exercise screen is shown, including reps, holds and each
+  /// side of a per side exercise.
+  pub exercise_start: Option<String>,
+  pub rest_start: Option<String>,
+  /// Run when the cooldown ends, but not when the workout is quit.
+  pub workout_complete: Option<String>,
+  /// Seconds a command can run for before it's stopped.
+  pub timeout_secs: u64,
+}
+
+impl Default for Hooks {
+  fn default() -> Self {
+    Hooks {
+      workout_start: None,
+      exercise_start: None,
+      rest_start: None,
+      workout_complete: None,
+      timeout_secs: 30,
+    }
+  }
+}
+
+/// A command to run, what it's for and the variables to run it with.
+#[derive(Debug, PartialEq)]
+struct Hook<'a> {
+  name: &'static str,
+  command: &'a str,
+  vars: Vec<(&'static str, String)>,
+}
+
+impl Hooks {
+  /// The hooks from the settings. When the settings can't be read none are
+  /// run, and the log says why.
+  pub fn load() -> Self {
+    match Settings::load() {
+      Ok(settings) => settings.hooks,
+      Err(e) => {
+        let log = state_path().join(LOG_FILE);
+        note(&log, &format!("no hooks were run - bad settings: {:#}", e));
+        Hooks::default()
+      }
+    }
+  }
+
+  /// The hook for an event in the workout with this title, if one is set.
+  fn hook(&self, title: &str, event: &Event) -> Option<Hook<'_>> {
+    let (name, command, mut vars) = match event {
+      Event::SessionStart { total_secs, elapsed_secs, .. } => {
+        let remaining = total_secs.saturating_sub(*elapsed_secs);
+        let vars = vec![("REMAINING_SECS", remaining.to_string())];
+        ("workout_start", &self.workout_start, vars)
+      }
+      Event::ScreenStart { screen_type, .. } if *screen_type == "rest" => {
+        ("rest_start", &self.rest_start, vec![])
+      }
+      Event::ScreenStart { screen_type, .. }
+        if !matches!(*screen_type, "warm_up" | "cooldown") =>
+      {
+        ("exercise_start", &self.exercise_start, vec![])
+      }
+      Event::Finish { elapsed_secs } => {
+        let vars = vec![("ELAPSED_SECS", elapsed_secs.to_string())];
+        ("workout_complete", &self.workout_complete, vars)
+      }
+      _ => return None,
+    };
+    let command = command.as_deref().filter(|c| !c.trim().is_empty())?;
+
+    vars.push(("WORKOUT_TITLE", title.to_string()));
+    vars.push(("WORKOUT_EVENT", name.to_string()));
+    if let Event::ScreenStart { screen_type, name, duration_secs, .. } = event {
+      vars.push(("SCREEN_TYPE", screen_type.to_string()));
+      vars.push(("EXERCISE_NAME", name.clone()));
+      // empty for reps that wait until they're done.
+      let remaining = duration_secs.map(|secs| secs.to_string());
+      vars.push(("REMAINING_SECS", remaining.unwrap_or_default()));
+    }
+    Some(Hook { name, command, vars })
+  }
+
+  /// Start the command for an event, if there is one, without waiting for
+  /// it. Anything that goes wrong is written to the log in the state
+  /// directory.
+  pub fn fire(&self, title: &str, event: &Event) {
+    let hook = match self.hook(title, event) {
+      Some(hook) => hook,
+      None => return,
+    };
+    let name = hook.name;
+    let command = hook.command.to_string();
+    let vars = hook.vars;
+    let timeout = Duration::from_secs(self.timeout_secs);
+    thread::spawn(move || {
+      let log = state_path().join(LOG_FILE);
+      if let Err(e) = run(&command, &vars, timeout, &log) {
+        note(&log, &format!("{} hook failed: {:#}", name, e));
+      }
+    });
+  }
+}
+
+/// Add a line to the log with the time. There is nowhere else to report
+/// things while the workout is running.
+fn note(log: &Path, message: &str) {
+  let _ = open_log(log).and_then(|mut file| {
+    let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+    writeln!(file, "{} {}", time, message)?;
+    Ok(())
+  });
+}
+
+fn open_log(path: &Path) -> Result<File> {
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir)?;
+  }
+  Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
+
+/// Run a command through the shell with its output going to the log,
+/// stopping it if it takes longer than the timeout.
+fn run(
+  command: &str,
+  vars: &[(&'static str, String)],
+  timeout: Duration,
+  log: &Path,
+) -> Result<()> {
+  let output = open_log(log)?;
+  let mut child = Command::new("sh")
+    .arg("-c")
+    .arg(command)
+    .envs(vars.iter().map(|(key, value)| (key, value)))
+    .stdin(Stdio::null())
+    .stdout(output.try_clone()?)
+    .stderr(output)
+    // in a group of its own so anything it starts can be stopped with it.
+    .process_group(0)
+    .spawn()?;
+
+  let started = Instant::now();
+  loop {
+    if let Some(status) = child.try_wait()? {
+      if !status.success() {
+        bail!("{:?} exited with {}", command, status);
+      }
+      return Ok(());
+    }
+    if started.elapsed() >= timeout {
+      unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+      child.wait()?;
+      bail!(
+        "{:?} was stopped after {} seconds",
+        command,
+        timeout.as_secs_f32()
+      );
+    }
+    thread::sleep(POLL);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lib::util::test_dir;
+
+  #[test]
+  fn it_picks_the_hook_for_an_event() {
+    let hooks = Hooks {
+      rest_start: Some("dim".to_string()),
+      exercise_start: Some("bright".to_string()),
+      ..Hooks::default()
+    };
+    let screen = |screen_type| Event::ScreenStart {
+      screen: 2,
+      screen_type,
+      heading: "SET 1".to_string(),
+      name: "Squats".to_string(),
+      duration_secs: Some(20),
+    };
+
+    let hook = hooks.hook("Legs", &screen("hold")).unwrap();
+    assert_eq!(hook.command, "bright");
+    assert!(hook.vars.contains(&("WORKOUT_TITLE", "Legs".to_string())));
+    assert!(hook.vars.contains(&("SCREEN_TYPE", "hold".to_string())));
+    assert!(hook.vars.contains(&("EXERCISE_NAME", "Squats".to_string())));
+    assert!(hook.vars.contains(&("REMAINING_SECS", "20".to_string())));
+    assert_eq!(hooks.hook("Legs", &screen("rest")).unwrap().command, "dim");
+    assert_eq!(hooks.hook("Legs", &screen("warm_up")), None);
+    assert_eq!(hooks.hook("Legs", &Event::Finish { elapsed_secs: 5 }), None);
+  }
+
+  #[test]
+  fn it_stops_slow_commands() {
+    let log = test_dir("hooks").join(LOG_FILE);
+    let vars = [("EXERCISE_NAME", "Squats".to_string())];
+    let timeout = Duration::from_millis(500);
+
+    run("echo \"$EXERCISE_NAME\"", &vars, timeout, &log).unwrap();
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "Squats\n");
+    assert!(run("exit 3", &vars, timeout, &log).is_err());
+    let started = Instant::now();
+    assert!(run("sleep 5; sleep 5", &vars, timeout, &log).is_err());
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    std::fs::remove_dir_all(log.parent().unwrap()).unwrap();
+  }
+}
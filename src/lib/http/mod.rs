@@ -0,0 +1,299 @@
+use crate::lib::{
+  control::{Control, Live, Remote},
+  enums::{DayOfWeek, ExerciseType},
+  workout::Workout,
+};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+  io::Write,
+  thread,
+  time::{Duration, Instant},
+};
+use tiny_http::{Header, Request, Response, Server};
+use workout_paths::data_path;
+
+/// A workout as listed by `GET /workouts`.
+#[derive(Serialize)]
+struct Summary<'a> {
+  title: &'a str,
+  day: DayOfWeek,
+  workout_type: ExerciseType,
+  minutes: u64,
+  tags: &'a [String],
+}
+
+/// What the server sends back: a status, a content type and a body.
+struct Reply {
+  status: u16,
+  content_type: &'static str,
+  body: String,
+}
+
+impl Reply {
+  fn json(status: u16, value: serde_json::Value) -> Self {
+    Reply { status, content_type: "application/json", body: value.to_string() }
+  }
+
+  fn error(status: u16, message: &str) -> Self {
+    Reply::json(status, json!({ "error": message }))
+  }
+}
+
+const USAGE: &str = "\
+GET  /display            a page showing the running workout, for a tablet or TV
+GET  /events             the running workout as Server-Sent Events
+GET  /workouts           the workouts in the data directory
+GET  /workouts/<title>   everything in a workout
+GET  /session            the screen being shown, or null if nothing is running
+POST /control/<control>  pause, next, previous, first, last, done or quit
+                         202 means it was passed on - like the keys, one that
+                         can't be done right then, such as next on the last
+                         screen, does nothing
+";
+
+/// The page behind `GET /display`. It needs nothing but this server.
+const DISPLAY: &str = include_str!("display.html");
+
+/// How often the event stream looks for something new to send.
+const POLL: Duration = Duration::from_millis(200);
+/// How long the event stream stays quiet before checking the browser is
+/// still there.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Decode the %XX escapes in a part of a URL.
+fn decode(part: &str) -> String {
+  let bytes = part.as_bytes();
+  let mut decoded = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+      u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+    });
+    match (bytes[i], hex) {
+      (b'%', Some(byte)) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      (byte, _) => {
+        decoded.push(byte);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Answer a request. The workouts are loaded only when they're asked for.
+fn route(
+  method: &str,
+  url: &str,
+  remote: &Remote,
+  workouts: impl FnOnce() -> Result<Vec<Workout>>,
+) -> Reply {
+  let path = url.split('?').next().unwrap_or_default();
+  let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+  match (method, parts.as_slice()) {
+    ("GET", [""]) => {
+      Reply { status: 200, content_type: "text/plain", body: USAGE.to_string() }
+    }
+    ("GET", ["display"]) => Reply {
+      status: 200,
+      content_type: "text/html",
+      body: DISPLAY.to_string(),
+    },
+    ("GET", ["workouts"]) => match workouts() {
+      Ok(workouts) => {
+        let summaries: Vec<Summary> = workouts
+          .iter()
+          .map(|w| Summary {
+            title: &w.title,
+            day: w.day,
+            workout_type: w.workout_type,
+            minutes: w.duration().as_secs() / 60,
+            tags: &w.tags,
+          })
+          .collect();
+        Reply::json(200, json!(summaries))
+      }
+      Err(e) => Reply::error(500, &e.to_string()),
+    },
+    ("GET", ["workouts", title]) => {
+      let title = decode(title);
+      match workouts() {
+        Ok(workouts) => match workouts.iter().find(|w| w.title == title) {
+          Some(workout) => Reply::json(200, json!(workout)),
+          None => Reply::error(404, &format!("No workout called {:?}", title)),
+        },
+        Err(e) => Reply::error(500, &e.to_string()),
+      }
+    }
+    ("GET", ["session"]) => Reply::json(200, json!(remote.live())),
+    ("POST", ["control", control]) => match control.parse::<Control>() {
+      Ok(_) if remote.live().is_none() => {
+        Reply::error(409, "No workout is running")
+      }
+      Ok(control) => {
+        remote.send(control);
+        Reply::json(202, json!({ "sent": control.to_string() }))
+      }
+      Err(_) => Reply::error(404, &format!("No control called {:?}", control)),
+    },
+    (_, ["display"])
+    | (_, ["events"])
+    | (_, ["workouts"])
+    | (_, ["workouts", _])
+    | (_, ["session"]) => Reply::error(405, "Use GET"),
+    (_, ["control", _]) => Reply::error(405, "Use POST"),
+    _ => Reply::error(404, "Not found - GET / lists what's here"),
+  }
+}
+
+/// A Server-Sent Event carrying what the runner is showing.
+fn event(live: &Option<Live>) -> String {
+  format!("data: {}\n\n", json!(live))
+}
+
+/// Send an event whenever what the runner is showing changes, until the
+/// browser goes away. The response is written straight to the connection
+/// since tiny_http would hold on to the events until it had a full chunk.
+fn stream(request: Request, remote: &Remote) -> std::io::Result<()> {
+  let mut writer = request.into_writer();
+  write!(
+    writer,
+    "HTTP/1.1 200 OK\r\n\
+     Content-Type: text/event-stream\r\n\
+     Cache-Control: no-cache\r\n\
+     Connection: close\r\n\r\n"
+  )?;
+  writer.flush()?;
+
+  let mut last = None;
+  let mut sent_at = Instant::now();
+  loop {
+    let event = event(&remote.live());
+    if last.as_ref() != Some(&event) {
+      writer.write_all(event.as_bytes())?;
+      last = Some(event);
+    } else if sent_at.elapsed() >= KEEP_ALIVE {
+      // a comment, which fails to send once the browser has gone.
+      writer.write_all(b": still there?\n\n")?;
+    } else {
+      thread::sleep(POLL);
+      continue;
+    }
+    writer.flush()?;
+    sent_at = Instant::now();
+  }
+}
+
+/// Answer a request, streaming events if they're asked for.
+fn respond(request: Request, remote: &Remote) {
+  if request.method().as_str() == "GET"
+    && request.url().split('?').next() == Some("/events")
+  {
+    // the browser has gone away - it will reconnect if it wants more.
+    let _ = stream(request, remote);
+    return;
+  }
+
+  let method = request.method().as_str();
+  let reply = if method != "GET"
+    && from_elsewhere(header(&request, "Origin"), header(&request, "Host"))
+  {
+    // a page on another site could otherwise pause or quit the workout.
+    Reply::error(403, "Controls can't be sent from other sites")
+  } else {
+    route(method, request.url(), remote, || {
+      Ok(Workout::load_dir(&data_path())?.0)
+    })
+  };
+  let response = Response::from_string(reply.body)
+    .with_status_code(reply.status)
+    .with_header(content_type(reply.content_type));
+  // the phone may have gone away - there's no one to tell.
+  let _ = request.respond(response);
+}
+
+/// Start serving the workouts and the running workout on a background
+/// thread, taking controls through the [Remote].
+pub fn start(address: &str) -> Result<()> {
+  let server = Server::http(address)
+    .map_err(|e| anyhow!("Couldn't listen on {}: {}", address, e))?;
+  let remote = Remote::install();
+  thread::spawn(move || {
+    for request in server.incoming_requests() {
+      // an event stream keeps its request open, so each gets a thread.
+      thread::spawn(move || respond(request, remote));
+    }
+  });
+  Ok(())
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+  let header = request.headers().iter().find(|h| h.field.equiv(name))?;
+  Some(header.value.as_str())
+}
+
+/// Whether a browser sent the request from a page that wasn't served here.
+/// Anything else, like curl, sends no `Origin`.
+fn from_elsewhere(origin: Option<&str>, host: Option<&str>) -> bool {
+  match (origin, host) {
+    (None, _) => false,
+    (Some(origin), Some(host)) => origin != format!("http://{}", host),
+    (Some(_), None) => true,
+  }
+}
+
+fn content_type(content_type: &str) -> Header {
+  let value = format!("{}; charset=utf-8", content_type);
+  Header::from_bytes("Content-Type", value).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_routes_requests() {
+    let remote = Remote::default();
+    let workouts = || Ok(vec![Workout::default()]);
+
+    let reply = route("GET", "/workouts/Default%20Workout", &remote, workouts);
+    assert_eq!(reply.status, 200);
+    assert!(reply.body.contains("\"warmup_length\":5"));
+    assert_eq!(route("GET", "/workouts/Nope", &remote, workouts).status, 404);
+    assert_eq!(route("GET", "/session", &remote, workouts).body, "null");
+
+    assert_eq!(route("POST", "/control/pause", &remote, workouts).status, 409);
+    remote.set(Some(Live::default()));
+    assert_eq!(route("POST", "/control/pause", &remote, workouts).status, 202);
+    assert_eq!(remote.try_recv(), Some(Control::Pause));
+    assert_eq!(route("POST", "/control/dance", &remote, workouts).status, 404);
+    assert_eq!(route("GET", "/control/next", &remote, workouts).status, 405);
+
+    assert!(!from_elsewhere(None, Some("laptop.local:8080")));
+    let here = Some("http://laptop.local:8080");
+    assert!(!from_elsewhere(here, Some("laptop.local:8080")));
+    assert!(from_elsewhere(Some("https://example.com"), Some("laptop:8080")));
+    assert!(from_elsewhere(Some("null"), Some("laptop.local:8080")));
+  }
+
+  #[test]
+  fn it_serves_the_display() {
+    let remote = Remote::default();
+    let workouts = || Ok(vec![]);
+
+    let reply = route("GET", "/display", &remote, workouts);
+    assert_eq!(reply.content_type, "text/html");
+    assert!(reply.body.contains("new EventSource(\"/events\")"));
+    assert_eq!(route("POST", "/events", &remote, workouts).status, 405);
+
+    assert_eq!(event(&None), "data: null\n\n");
+    let live = Live { title: "Legs".to_string(), ..Live::default() };
+    let event = event(&Some(live));
+    assert!(event.starts_with("data: {") && event.ends_with("}\n\n"));
+    assert_eq!(event.matches('\n').count(), 2);
+  }
+}
@@ -1,10 +1,15 @@
-#[derive(Clone)]
-pub struct Action {
-  pub run: &'static dyn Fn(),
+/// What happens when a menu item is chosen. The result of running it is
+/// handed back from the menu.
+pub struct Action<'a, T> {
+  run: Box<dyn Fn() -> T + 'a>,
 }
 
-impl Action {
-  pub fn new(run: &'static dyn Fn()) -> Self {
-    Self { run }
+impl<'a, T> Action<'a, T> {
+  pub fn new(run: impl Fn() -> T + 'a) -> Self {
+    Self { run: Box::new(run) }
+  }
+
+  pub fn run(&self) -> T {
+    (self.run)()
   }
 }
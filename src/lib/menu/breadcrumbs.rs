@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+/// The trail of pages that led to the current one. Going back returns to the
+/// page before, and going back from the first page leaves the menus.
+pub struct Breadcrumbs<P> {
+  pages: Vec<P>,
+}
+
+impl<P: Display> Breadcrumbs<P> {
+  pub fn new(first: P) -> Self {
+    Self { pages: vec![first] }
+  }
+
+  /// The page being shown, or `None` once every page has been left.
+  pub fn current(&self) -> Option<&P> {
+    self.pages.last()
+  }
+
+  pub fn push(&mut self, page: P) {
+    self.pages.push(page);
+  }
+
+  pub fn back(&mut self) {
+    self.pages.pop();
+  }
+
+  /// Leave every page.
+  pub fn clear(&mut self) {
+    self.pages.clear();
+  }
+
+  /// The titles of every page, for example `Workouts > Monday`.
+  pub fn trail(&self) -> String {
+    self.pages.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" > ")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_goes_back_through_the_trail() {
+    let mut crumbs = Breadcrumbs::new("Workouts");
+    crumbs.push("Monday");
+    crumbs.push("Workout 1.1");
+    assert_eq!(crumbs.trail(), "Workouts > Monday > Workout 1.1");
+
+    crumbs.back();
+    assert_eq!(crumbs.current(), Some(&"Monday"));
+    crumbs.back();
+    crumbs.back();
+    assert_eq!(crumbs.current(), None);
+  }
+}
@@ -1,20 +1,19 @@
 use crate::lib::menu::action::Action;
 
-#[derive(Clone)]
-pub struct MenuItem {
+pub struct MenuItem<'a, T> {
   pub title: String,
-  pub action: Action,
+  pub action: Action<'a, T>,
 }
 
-impl PartialEq<Self> for MenuItem {
+impl<'a, T> PartialEq<Self> for MenuItem<'a, T> {
   fn eq(&self, other: &Self) -> bool {
     self.title == other.title
   }
 }
 
-impl MenuItem {
-  pub fn new(title: String, action: &'static dyn Fn()) -> Self {
+impl<'a, T> MenuItem<'a, T> {
+  pub fn new<S: Into<String>>(title: S, action: impl Fn() -> T + 'a) -> Self {
     let action = Action::new(action);
-    Self { title, action }
+    Self { title: title.into(), action }
   }
 }
@@ -1,30 +1,109 @@
 pub mod action;
+pub mod breadcrumbs;
 pub mod item;
 
+use crate::lib::util::clear_screen;
 use item::MenuItem;
 
-#[derive(Clone)]
-pub struct Menu {
+const BACK: &str = "..";
+const QUIT: &str = "Quit";
+
+/// What was chosen from a menu.
+#[derive(Debug, PartialEq)]
+pub enum Selection<T> {
+  /// An item was chosen and its action returned this.
+  Chose(T),
+  /// `..` or Esc was chosen.
+  Back,
+  Quit,
+}
+
+/// A menu of items shown with a heading and optionally some lines of text.
+/// Items are told apart by their position rather than their title, so any
+/// title can be used.
+pub struct Menu<'a, T> {
   pub heading: String,
-  pub items: Vec<MenuItem>,
+  pub lines: Vec<String>,
+  pub items: Vec<MenuItem<'a, T>>,
+  /// Whether to show `..` to go back to the previous menu.
+  pub back: bool,
+  /// Whether to show Quit at the bottom of the menu.
+  pub quit: bool,
 }
 
-impl Menu {
+impl<'a, T> Menu<'a, T> {
+  pub fn new<S: Into<String>>(heading: S) -> Self {
+    Self {
+      heading: heading.into(),
+      lines: vec![],
+      items: vec![],
+      back: true,
+      quit: true,
+    }
+  }
+
+  /// Add a line of text shown under the heading.
+  pub fn line<S: Into<String>>(mut self, line: S) -> Self {
+    self.lines.push(line.into());
+    self
+  }
+
+  /// Add an item that runs `action` when chosen.
+  pub fn item<S: Into<String>>(
+    mut self,
+    title: S,
+    action: impl Fn() -> T + 'a,
+  ) -> Self {
+    self.items.push(MenuItem::new(title, action));
+    self
+  }
+
+  /// Don't show `..`, for the first menu.
+  pub fn without_back(mut self) -> Self {
+    self.back = false;
+    self
+  }
+
+  /// Don't show Quit.
+  pub fn without_quit(mut self) -> Self {
+    self.quit = false;
+    self
+  }
+
   fn menu(&self) -> terminal_menu::TerminalMenu {
-    let heading = terminal_menu::label(&self.heading);
-    let mut heading = vec![heading];
-    let mut items: Vec<terminal_menu::TerminalMenuItem> =
-      self.items.iter().map(|i| terminal_menu::button(&i.title)).collect();
-    heading.append(&mut items);
-    terminal_menu::menu(heading)
+    let mut list = vec![terminal_menu::label(&self.heading)];
+    list.extend(self.lines.iter().map(terminal_menu::label));
+    if self.back {
+      list.push(terminal_menu::button(BACK));
+    }
+    list.extend(self.items.iter().map(|i| terminal_menu::button(&i.title)));
+    if self.quit {
+      list.push(terminal_menu::button(QUIT));
+    }
+    terminal_menu::menu(list)
   }
 
-  pub fn run(&self) -> &MenuItem {
+  /// Show the menu and run the action of the chosen item.
+  pub fn run(&self) -> Selection<T> {
     let menu = self.menu();
+    print!("{}", clear_screen());
     terminal_menu::run(&menu);
-    let index = terminal_menu::mut_menu(&menu).selected_item_index();
-    let item = &self.items[index];
-    (item.action.run)();
-    item
+    let (canceled, selected) = {
+      let menu = terminal_menu::mut_menu(&menu);
+      (menu.canceled(), menu.selected_item_index())
+    };
+    if canceled {
+      return Selection::Back;
+    }
+
+    // skip past the heading, lines and back button.
+    let first_item = 1 + self.lines.len() + if self.back { 1 } else { 0 };
+    match selected.checked_sub(first_item) {
+      None => Selection::Back,
+      Some(index) => match self.items.get(index) {
+        Some(item) => Selection::Chose(item.action.run()),
+        None => Selection::Quit,
+      },
+    }
   }
 }
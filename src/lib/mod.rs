@@ -1,6 +1,16 @@
+pub mod cli;
+pub mod control;
 pub mod enums;
+pub mod events;
+pub mod export;
+pub mod file_format;
+pub mod history;
+pub mod hooks;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod menu;
 pub mod screen;
+pub mod settings;
 pub mod timer;
 pub mod util;
 pub mod workout;
-pub mod menu;
\ No newline at end of file
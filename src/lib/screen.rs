@@ -3,7 +3,11 @@ use std::time::Duration;
 
 use termion::{color, cursor};
 
-use super::workout::exercise::{Exercise, ExerciseSet};
+use super::history::{Interval, IntervalKind, Outcome};
+use super::workout::{
+  exercise::{Exercise, ExerciseSet},
+  prescription::{Prescription, Side},
+};
 
 /// [WARMUP] is a constant exercise that is shown during the warmup period.
 fn warmup() -> Exercise {
@@ -38,6 +42,11 @@ pub enum ScreenType {
   WarmUp(Duration),
   Rest(Duration),
   Exercise(usize, Duration),
+  /// A number of reps with an optional time cap. Without a cap the screen
+  /// waits for a key press.
+  Reps(usize, u32, Option<Duration>),
+  Hold(usize, Duration),
+  PerSide(usize, Side, Duration),
   Cooldown(Duration),
 }
 
@@ -58,17 +67,93 @@ impl ScreenType {
     ScreenType::Cooldown(Duration::from_secs(60 * 10))
   }
 
-  pub fn duration(&self) -> &Duration {
+  /// The screen types for an exercise with the given [Prescription]. Per side
+  /// exercises are split into one screen for each side.
+  pub fn prescribed(id: usize, prescription: &Prescription) -> Vec<Self> {
+    match prescription {
+      Prescription::Timed => vec![ScreenType::exercise(id)],
+      Prescription::Reps { count, cap } => {
+        vec![ScreenType::Reps(id, *count, cap.map(Duration::from_secs))]
+      }
+      Prescription::Hold { secs } => {
+        vec![ScreenType::Hold(id, Duration::from_secs(*secs))]
+      }
+      Prescription::PerSide { secs } => vec![
+        ScreenType::PerSide(id, Side::First, Duration::from_secs(*secs)),
+        ScreenType::PerSide(id, Side::Second, Duration::from_secs(*secs)),
+      ],
+    }
+  }
+
+  /// The heading shown above the screen.
+  pub fn heading(&self) -> String {
+    match self {
+      ScreenType::WarmUp(_) => "WARMING UP!".to_string(),
+      ScreenType::Rest(_) => "REST!".to_string(),
+      ScreenType::Exercise(i, _) => format!("SET {}", i),
+      ScreenType::Reps(i, count, _) => format!("SET {} - {} REPS", i, count),
+      ScreenType::Hold(i, _) => format!("SET {} - HOLD", i),
+      ScreenType::PerSide(i, side, _) => format!("SET {} - {}", i, side),
+      ScreenType::Cooldown(_) => "Aah - Feel better?".to_string(),
+    }
+  }
+
+  /// The name of the type of screen, as it's given in the event stream.
+  pub fn name(&self) -> &'static str {
+    match self {
+      ScreenType::WarmUp(_) => "warm_up",
+      ScreenType::Rest(_) => "rest",
+      ScreenType::Exercise(..) => "exercise",
+      ScreenType::Reps(..) => "reps",
+      ScreenType::Hold(..) => "hold",
+      ScreenType::PerSide(..) => "per_side",
+      ScreenType::Cooldown(_) => "cooldown",
+    }
+  }
+
+  /// The length of the screen. Reps without a cap have no set length.
+  pub fn duration(&self) -> Duration {
     match self {
-      ScreenType::WarmUp(d) => d,
-      ScreenType::Rest(d) => d,
-      ScreenType::Exercise(_, d) => d,
-      ScreenType::Cooldown(d) => d,
+      ScreenType::WarmUp(d) => *d,
+      ScreenType::Rest(d) => *d,
+      ScreenType::Exercise(_, d) => *d,
+      ScreenType::Reps(_, _, d) => d.unwrap_or_default(),
+      ScreenType::Hold(_, d) => *d,
+      ScreenType::PerSide(_, _, d) => *d,
+      ScreenType::Cooldown(d) => *d,
     }
   }
+
+  /// Whether the screen only advances when a key is pressed.
+  pub fn waits_for_key(&self) -> bool {
+    matches!(self, ScreenType::Reps(_, _, None))
+  }
+
+  /// The kind of interval the screen is recorded as in the history.
+  pub fn kind(&self) -> IntervalKind {
+    match self {
+      ScreenType::WarmUp(_) => IntervalKind::WarmUp,
+      ScreenType::Rest(_) => IntervalKind::Rest,
+      ScreenType::Cooldown(_) => IntervalKind::Cooldown,
+      _ => IntervalKind::Exercise,
+    }
+  }
+
+  /// Whether the screen is one of the exercises in a set.
+  pub fn is_exercise(&self) -> bool {
+    matches!(
+      self,
+      ScreenType::Exercise(..)
+        | ScreenType::Reps(..)
+        | ScreenType::Hold(..)
+        | ScreenType::PerSide(..)
+    )
+  }
 }
 
 pub struct Screen {
+  /// The name of the exercise being done on the screen.
+  pub name: String,
   pub output: String,
   pub screen_type: ScreenType,
 }
@@ -79,8 +164,9 @@ impl Screen {
     let screen_type = ScreenType::WarmUp(Duration::from_secs(duration));
 
     let mut set = set.clone();
-    set.select(4);
-    output += warmup().to_string().as_str();
+    set.select(0);
+    let warmup = warmup();
+    output += warmup.to_string().as_str();
     output += format!(
       "{}{}UP NEXT:{}",
       cursor::Left(u16::MAX),
@@ -90,34 +176,32 @@ impl Screen {
     .as_str();
     output += set.to_string().as_str();
 
-    Screen { output, screen_type }
+    Screen { name: warmup.name, output, screen_type }
   }
 
-  pub fn exercise_set_with_rest(set: &ExerciseSet, id: u8) -> Self {
-    let mut output = String::new();
-    let screen_type = ScreenType::exercise(id as usize);
-
-    let mut set = set.clone();
-    set.select(id);
-    output += set.to_string().as_str();
-    output += format!(
-      "{}{}UP NEXT:{}",
-      cursor::Left(u16::MAX),
-      color::Fg(color::Red),
-      color::Fg(color::Reset)
-    )
-    .as_str();
-    output += rest().to_string().as_str();
-
-    Screen { output, screen_type }
+  pub fn exercise_set_with_rest(set: &ExerciseSet, id: u8) -> Vec<Self> {
+    Screen::exercise_set_with_next(set, id, rest())
   }
 
-  pub fn exercise_set_with_cooldown(set: &ExerciseSet, id: u8) -> Self {
-    let mut output = String::new();
-    let screen_type = ScreenType::exercise(id as usize);
+  pub fn exercise_set_with_cooldown(set: &ExerciseSet, id: u8) -> Vec<Self> {
+    Screen::exercise_set_with_next(set, id, cooldown())
+  }
 
+  /// The screens for a single exercise in a set, one for each screen type
+  /// its [Prescription] calls for.
+  fn exercise_set_with_next(
+    set: &ExerciseSet,
+    id: u8,
+    next: Exercise,
+  ) -> Vec<Self> {
     let mut set = set.clone();
     set.select(id);
+    let (name, prescription) = match set.get(id) {
+      Some(exercise) => (exercise.name.clone(), exercise.prescription.clone()),
+      None => (String::new(), Prescription::default()),
+    };
+
+    let mut output = String::new();
     output += set.to_string().as_str();
     output += format!(
       "{}{}UP NEXT:{}",
@@ -126,18 +210,26 @@ impl Screen {
       color::Fg(color::Reset)
     )
     .as_str();
-    output += cooldown().to_string().as_str();
+    output += next.to_string().as_str();
 
-    Screen { output, screen_type }
+    ScreenType::prescribed(id as usize, &prescription)
+      .into_iter()
+      .map(|screen_type| Screen {
+        name: name.clone(),
+        output: output.clone(),
+        screen_type,
+      })
+      .collect()
   }
 
   pub fn cooldown() -> Self {
     let mut output = String::new();
     let screen_type = ScreenType::cooldown();
 
-    output += cooldown().to_string().as_str();
+    let cooldown = cooldown();
+    output += cooldown.to_string().as_str();
 
-    Screen { output, screen_type }
+    Screen { name: cooldown.name, output, screen_type }
   }
 
   pub fn rest_with_set(set: &ExerciseSet) -> Self {
@@ -145,8 +237,9 @@ impl Screen {
     let screen_type = ScreenType::rest();
 
     let mut set = set.clone();
-    set.select(4);
-    output += rest().to_string().as_str();
+    set.select(0);
+    let rest = rest();
+    output += rest.to_string().as_str();
     output += format!(
       "{}{}UP NEXT:{}",
       cursor::Left(u16::MAX),
@@ -156,7 +249,18 @@ impl Screen {
     .as_str();
     output += set.to_string().as_str();
 
-    Screen { output, screen_type }
+    Screen { name: rest.name, output, screen_type }
+  }
+
+  /// Record the time spent on this screen.
+  pub fn interval(&self, actual_secs: u64, outcome: Outcome) -> Interval {
+    Interval {
+      kind: self.screen_type.kind(),
+      name: self.name.clone(),
+      planned_secs: self.screen_type.duration().as_secs(),
+      actual_secs,
+      outcome,
+    }
   }
 }
 
@@ -165,3 +269,25 @@ impl fmt::Display for Screen {
     write!(f, "{}{}", self.output, cursor::Left(u16::MAX))
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_splits_per_side_exercises_into_two_screens() {
+    let types = ScreenType::prescribed(1, &Prescription::PerSide { secs: 30 });
+    assert_eq!(types.len(), 2);
+    assert_eq!(types[0].duration(), Duration::from_secs(30));
+    assert_eq!(types[1].duration(), Duration::from_secs(30));
+  }
+
+  #[test]
+  fn it_waits_for_a_key_on_uncapped_reps() {
+    let uncapped = ScreenType::Reps(1, 15, None);
+    let capped = ScreenType::Reps(1, 15, Some(Duration::from_secs(60)));
+    assert!(uncapped.waits_for_key());
+    assert!(!capped.waits_for_key());
+    assert_eq!(capped.duration(), Duration::from_secs(60));
+  }
+}
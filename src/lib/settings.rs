@@ -0,0 +1,56 @@
+use crate::lib::{
+  file_format::FileFormat, history::progression::ProgressionRule, hooks::Hooks,
+};
+use anyhow::Result;
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use serde_yaml::from_reader;
+use std::fs::File;
+use workout_paths::settings_path;
+
+/// User settings loaded from the settings file in the config directory. Any
+/// setting that is missing from the file uses its default.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Settings {
+  /// How the next load is suggested in the exercise history.
+  pub progression: ProgressionRule,
+  /// Interrupted workouts younger than this many minutes are offered to be
+  /// resumed on the next launch.
+  pub resume_max_age_mins: i64,
+  /// The time of day workouts are planned for in the calendar export.
+  pub workout_time: NaiveTime,
+  /// The format new and edited workouts are saved in.
+  pub save_format: FileFormat,
+  /// Where to serve the workouts and the running workout over HTTP, such as
+  /// `0.0.0.0:8080`. Only used when built with the `http` feature.
+  pub http_address: Option<String>,
+  /// Shell commands to run when a workout starts, an exercise or rest
+  /// starts, or the workout is complete.
+  pub hooks: Hooks,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Settings {
+      progression: ProgressionRule::default(),
+      resume_max_age_mins: 120,
+      workout_time: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+      save_format: FileFormat::default(),
+      http_address: None,
+      hooks: Hooks::default(),
+    }
+  }
+}
+
+impl Settings {
+  pub fn load() -> Result<Self> {
+    let path = settings_path();
+    if !path.exists() {
+      return Ok(Settings::default());
+    }
+    let f = File::open(path)?;
+    let result: Settings = from_reader(f)?;
+    Ok(result)
+  }
+}
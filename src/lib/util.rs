@@ -1,6 +1,15 @@
 use anyhow::Result;
-use std::io::{Read, Write};
-use termion::{clear, cursor};
+use std::{
+  io::{self, Read, Write},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver, TryRecvError},
+    Arc,
+  },
+  thread::spawn,
+  time::Duration,
+};
+use termion::{clear, cursor, event::Key, input::TermRead};
 
 pub fn clear_screen() -> String {
   format!("{}{}", clear::All, cursor::Goto(1, 1))
@@ -11,7 +20,6 @@ pub fn just_left() -> String {
 }
 
 pub fn pause() -> Result<()> {
-  use std::io::{self};
   let mut stdin = io::stdin();
   let mut stdout = io::stdout();
 
@@ -22,3 +30,101 @@ pub fn pause() -> Result<()> {
 
   Ok(())
 }
+
+/// Ask a question and return the trimmed line that was typed in.
+pub fn prompt(question: &str) -> Result<String> {
+  let mut stdout = io::stdout();
+
+  write!(stdout, "{}", question)?;
+  stdout.flush()?;
+
+  let mut line = String::new();
+  io::stdin().read_line(&mut line)?;
+
+  Ok(line.trim().to_owned())
+}
+
+/// Reads key presses from stdin on a background thread. The thread stops
+/// when the reader is dropped so it doesn't swallow input meant for whatever
+/// reads stdin next.
+pub struct KeyReader {
+  rx: Receiver<Key>,
+  stop: Arc<AtomicBool>,
+}
+
+impl KeyReader {
+  pub fn spawn() -> Self {
+    let (tx, rx) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+
+    spawn(move || {
+      let mut buf = [0u8; 64];
+      while !stopped.load(Ordering::Relaxed) {
+        if !stdin_ready(Duration::from_millis(100)) {
+          continue;
+        }
+        // read straight from the descriptor and use every key read. Going
+        // through Stdin would leave the rest of a quick burst of keys in its
+        // buffer, where poll can't see them.
+        let read = match read_stdin(&mut buf) {
+          Ok(0) => return,
+          Ok(read) => read,
+          Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+          Err(_) => return,
+        };
+        for key in buf[..read].keys().flatten() {
+          if tx.send(key).is_err() {
+            return;
+          }
+        }
+      }
+    });
+
+    KeyReader { rx, stop }
+  }
+
+  pub fn try_recv(&self) -> Result<Key, TryRecvError> {
+    self.rx.try_recv()
+  }
+}
+
+impl Drop for KeyReader {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::Relaxed);
+  }
+}
+
+/// Read whatever is waiting on stdin, without any buffering in between.
+fn read_stdin(buf: &mut [u8]) -> io::Result<usize> {
+  // SAFETY: the buffer is valid for writes of its whole length.
+  let read = unsafe {
+    libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len())
+  };
+  if read < 0 {
+    return Err(io::Error::last_os_error());
+  }
+  Ok(read as usize)
+}
+
+/// Wait up to `timeout` for stdin to have something to read.
+fn stdin_ready(timeout: Duration) -> bool {
+  let mut fd =
+    libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+  // SAFETY: poll is given a single valid pollfd that outlives the call.
+  unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+/// An empty directory for a test to work in. The process id is part of its
+/// name so runs at the same time don't share it.
+#[cfg(test)]
+pub fn test_dir(name: &str) -> std::path::PathBuf {
+  let dir = std::env::temp_dir().join(format!(
+    "workout-{}-{}",
+    name,
+    std::process::id()
+  ));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).unwrap();
+  dir
+}
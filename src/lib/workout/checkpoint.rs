@@ -0,0 +1,95 @@
+use super::{Start, Workout};
+use crate::lib::history::RunStats;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{from_reader, to_writer};
+use std::{fs::File, path::PathBuf};
+use workout_paths::state_path;
+
+/// Where a running workout is up to. The runner saves it to the state
+/// directory every few seconds so an interrupted workout can be resumed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Checkpoint {
+  pub title: String,
+  /// When the workout was first started.
+  pub started: DateTime<Local>,
+  /// When the checkpoint was last saved.
+  pub saved: DateTime<Local>,
+  /// The index of the current screen.
+  pub screen: usize,
+  /// The number of seconds into the current screen.
+  pub current_time: u64,
+  #[serde(flatten)]
+  pub stats: RunStats,
+}
+
+impl Checkpoint {
+  /// How often the runner saves the checkpoint, in seconds.
+  pub const INTERVAL_SECS: u64 = 5;
+
+  const FILE: &'static str = "checkpoint.yml";
+
+  /// A checkpoint for a workout that hasn't been started yet.
+  pub fn new(
+    workout: &Workout,
+    start: Start,
+    started: DateTime<Local>,
+  ) -> Self {
+    let screen = workout.start_screen(start);
+    let planned_secs =
+      workout.duration().as_secs() - workout.time_before(screen);
+    Checkpoint {
+      title: workout.title.clone(),
+      started,
+      saved: started,
+      screen,
+      current_time: 0,
+      stats: RunStats { planned_secs, ..RunStats::default() },
+    }
+  }
+
+  pub fn path() -> PathBuf {
+    state_path().join(Checkpoint::FILE)
+  }
+
+  /// Move the checkpoint up to where the runner is.
+  pub fn update(&mut self, screen: usize, current_time: u64, stats: &RunStats) {
+    self.screen = screen;
+    self.current_time = current_time;
+    self.stats = stats.clone();
+  }
+
+  pub fn save(&mut self) -> Result<()> {
+    self.saved = Local::now();
+    std::fs::create_dir_all(state_path())?;
+    let f = File::create(Checkpoint::path())?;
+    to_writer(f, &self)?;
+    Ok(())
+  }
+
+  /// Load the last checkpoint, if there is one.
+  pub fn load() -> Result<Option<Self>> {
+    let path = Checkpoint::path();
+    if !path.exists() {
+      return Ok(None);
+    }
+    let f = File::open(path)?;
+    let result: Checkpoint = from_reader(f)?;
+    Ok(Some(result))
+  }
+
+  /// Remove the saved checkpoint once it is no longer needed.
+  pub fn clear() -> Result<()> {
+    let path = Checkpoint::path();
+    if path.exists() {
+      std::fs::remove_file(path)?;
+    }
+    Ok(())
+  }
+
+  /// How long ago the checkpoint was saved, in minutes.
+  pub fn age_mins(&self) -> i64 {
+    (Local::now() - self.saved).num_minutes()
+  }
+}
@@ -0,0 +1,268 @@
+use super::Workout;
+use crate::lib::{file_format::FileFormat, settings::Settings};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{from_reader, to_writer};
+use std::{
+  fs::File,
+  path::{Path, PathBuf},
+};
+use workout_paths::{data_path, state_path, trash_path};
+
+/// The file in the state directory holding what the last save replaced.
+pub const UNDO_FILE: &str = "undo.yml";
+
+/// What the last save from the editor replaced, so it can be put back.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Undo {
+  /// The title the workout was saved under.
+  pub saved: String,
+  /// The workout as it was before, or `None` if it was new.
+  pub previous: Option<Workout>,
+}
+
+/// The file in a directory holding the workout with the given title,
+/// whatever its format.
+fn find_in(dir: &Path, title: &str) -> Option<PathBuf> {
+  Workout::files_in(dir, title).into_iter().next()
+}
+
+/// Changes the workouts in the data directory, keeping what each save
+/// replaced so the last one can be undone. Deleted workouts go to the trash
+/// where they can be restored from.
+pub struct Edits {
+  pub data_dir: PathBuf,
+  pub trash_dir: PathBuf,
+  pub undo_file: PathBuf,
+  pub format: FileFormat,
+}
+
+impl Edits {
+  pub fn new() -> Result<Self> {
+    Ok(Edits {
+      data_dir: data_path(),
+      trash_dir: trash_path(),
+      undo_file: state_path().join(UNDO_FILE),
+      format: Settings::load()?.save_format,
+    })
+  }
+
+  /// The file holding the workout with the given title, whatever its format.
+  pub fn find(&self, title: &str) -> Option<PathBuf> {
+    find_in(&self.data_dir, title)
+  }
+
+  fn remove(&self, title: &str) -> Result<()> {
+    while let Some(path) = self.find(title) {
+      std::fs::remove_file(path)?;
+    }
+    Ok(())
+  }
+
+  /// Load the workout with the given title.
+  pub fn load(&self, title: &str) -> Result<Workout> {
+    match self.find(title) {
+      Some(path) => Workout::load_file(&path),
+      None => bail!("There's no workout called {:?}", title),
+    }
+  }
+
+  /// Save a copy of a workout under a new title.
+  pub fn duplicate(&self, title: &str, new_title: &str) -> Result<Workout> {
+    let mut copy = self.load(title)?;
+    copy.title = new_title.to_string();
+    self.save(&copy, None)?;
+    Ok(copy)
+  }
+
+  /// Give a workout a new title. Like any other save this can be undone.
+  pub fn rename(&self, title: &str, new_title: &str) -> Result<()> {
+    let mut workout = self.load(title)?;
+    workout.title = new_title.to_string();
+    self.save(&workout, Some(title))
+  }
+
+  /// Move a workout to the trash, replacing any copy of it already there. If
+  /// the last save was of this workout it can't be undone any more.
+  pub fn delete(&self, title: &str) -> Result<()> {
+    let path = match self.find(title) {
+      Some(path) => path,
+      None => bail!("There's no workout called {:?}", title),
+    };
+    while let Some(old) = find_in(&self.trash_dir, title) {
+      std::fs::remove_file(old)?;
+    }
+    std::fs::create_dir_all(&self.trash_dir)?;
+    std::fs::rename(&path, self.trash_dir.join(path.file_name().unwrap()))?;
+    self.remove(title)?;
+    if self.last()?.is_some_and(|undo| undo.saved == title) {
+      std::fs::remove_file(&self.undo_file)?;
+    }
+    Ok(())
+  }
+
+  /// The titles of the workouts in the trash.
+  pub fn trashed(&self) -> Result<Vec<String>> {
+    if !self.trash_dir.exists() {
+      return Ok(vec![]);
+    }
+    let (workouts, _) = Workout::load_dir(&self.trash_dir)?;
+    Ok(workouts.into_iter().map(|w| w.title).collect())
+  }
+
+  /// Move a workout back out of the trash.
+  pub fn restore(&self, title: &str) -> Result<()> {
+    let path = match find_in(&self.trash_dir, title) {
+      Some(path) => path,
+      None => bail!("There's no workout called {:?} in the trash", title),
+    };
+    if self.find(title).is_some() {
+      bail!("There's already a workout called {:?} - rename it first", title);
+    }
+    std::fs::rename(&path, self.data_dir.join(path.file_name().unwrap()))?;
+    Ok(())
+  }
+
+  /// Save an edited workout. `original` is the title it was loaded with, or
+  /// `None` for a new workout, so a renamed workout replaces its old file.
+  pub fn save(&self, workout: &Workout, original: Option<&str>) -> Result<()> {
+    workout.validate()?;
+    if original != Some(workout.title.as_str())
+      && self.find(&workout.title).is_some()
+    {
+      bail!("There's already a workout called {:?}", workout.title);
+    }
+
+    let previous = match original.and_then(|title| self.find(title)) {
+      Some(path) => Some(Workout::load_file(&path)?),
+      None => None,
+    };
+    let undo = Undo { saved: workout.title.clone(), previous };
+    if let Some(dir) = self.undo_file.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+    to_writer(File::create(&self.undo_file)?, &undo)?;
+
+    workout.save_to(&self.data_dir, self.format)?;
+    match original {
+      Some(title) if title != workout.title => self.remove(title),
+      _ => Ok(()),
+    }
+  }
+
+  /// What the last save replaced, if it hasn't been undone yet.
+  pub fn last(&self) -> Result<Option<Undo>> {
+    if !self.undo_file.exists() {
+      return Ok(None);
+    }
+    Ok(Some(from_reader(File::open(&self.undo_file)?)?))
+  }
+
+  /// Put back what the last save replaced, returning what was done.
+  pub fn undo(&self) -> Result<Option<String>> {
+    let undo = match self.last()? {
+      Some(undo) => undo,
+      None => return Ok(None),
+    };
+    if self.find(&undo.saved).is_none() {
+      bail!("{:?} isn't saved any more, so it can't be undone", undo.saved);
+    }
+    let done = match undo.previous {
+      Some(previous) => {
+        previous.save_to(&self.data_dir, self.format)?;
+        if previous.title != undo.saved {
+          self.remove(&undo.saved)?;
+        }
+        format!("Put back {:?} as it was before the last save", previous.title)
+      }
+      None => {
+        self.remove(&undo.saved)?;
+        format!("Removed the new workout {:?}", undo.saved)
+      }
+    };
+    std::fs::remove_file(&self.undo_file)?;
+    Ok(Some(done))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lib::{util::test_dir, workout::prescription::Prescription};
+
+  fn edits(name: &str) -> Edits {
+    let root = test_dir(&format!("edit-{}", name));
+    Edits {
+      data_dir: root.join("data"),
+      trash_dir: root.join("data").join("trash"),
+      undo_file: root.join("state").join(UNDO_FILE),
+      format: FileFormat::Yaml,
+    }
+  }
+
+  #[test]
+  fn it_undoes_the_last_save() {
+    let edits = edits("undo");
+    let mut workout = Workout::default();
+    edits.save(&workout, None).unwrap();
+    assert!(edits.save(&workout, None).is_err());
+
+    workout.title = "Renamed".to_string();
+    workout.warmup_length = 10;
+    edits.save(&workout, Some("Default Workout")).unwrap();
+    assert!(edits.find("Default Workout").is_none());
+
+    edits.undo().unwrap().unwrap();
+    assert!(edits.find("Renamed").is_none());
+    let path = edits.find("Default Workout").unwrap();
+    assert_eq!(Workout::load_file(&path).unwrap().warmup_length, 5);
+    assert!(edits.undo().unwrap().is_none());
+
+    workout.sets[0].exercises[0].prescription = Prescription::Hold { secs: 0 };
+    assert!(edits.save(&workout, Some("Renamed")).is_err());
+    workout.sets[0].exercises.clear();
+    assert!(edits.save(&workout, None).is_err());
+  }
+
+  #[test]
+  fn it_duplicates_renames_deletes_and_restores() {
+    let edits = edits("manage");
+    edits.save(&Workout::default(), None).unwrap();
+    edits.duplicate("Default Workout", "Copy").unwrap();
+    assert!(edits.duplicate("Default Workout", "Copy").is_err());
+    assert!(edits.rename("Copy", "Default Workout").is_err());
+    edits.rename("Copy", "Moved").unwrap();
+    assert!(edits.find("Copy").is_none());
+
+    edits.delete("Moved").unwrap();
+    assert!(edits.find("Moved").is_none());
+    // undoing the rename would put it back while it's in the trash.
+    assert!(edits.last().unwrap().is_none());
+    assert_eq!(edits.trashed().unwrap(), vec!["Moved".to_string()]);
+    // the trash isn't read as a workout.
+    let (workouts, _) = Workout::load_dir(&edits.data_dir).unwrap();
+    assert_eq!(workouts.len(), 1);
+
+    edits.restore("Moved").unwrap();
+    assert_eq!(edits.load("Moved").unwrap().title, "Moved");
+    assert!(edits.trashed().unwrap().is_empty());
+    assert!(edits.restore("Moved").is_err());
+
+    // a workout saved as .yaml by hand is found and replaced too.
+    let yaml = edits.data_dir.join("By hand.yaml");
+    let workout =
+      Workout { title: "By hand".to_string(), ..Workout::default() };
+    FileFormat::Yaml.write(&yaml, &workout).unwrap();
+    assert_eq!(edits.find("By hand"), Some(yaml.clone()));
+    workout.save_to(&edits.data_dir, FileFormat::Json).unwrap();
+    assert!(!yaml.exists());
+    let (workouts, _) = Workout::load_dir(&edits.data_dir).unwrap();
+    assert_eq!(workouts.len(), 3);
+
+    // a save that can't be written leaves the old file where it was.
+    std::fs::create_dir(edits.data_dir.join("By hand.toml")).unwrap();
+    assert!(workout.save_to(&edits.data_dir, FileFormat::Toml).is_err());
+    assert!(edits.data_dir.join("By hand.json").exists());
+    assert!(!edits.data_dir.join(".By hand.toml.tmp").exists());
+  }
+}
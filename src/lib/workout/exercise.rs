@@ -1,33 +1,76 @@
-use crate::lib::enums::ExerciseType;
+use crate::lib::{
+  enums::ExerciseType,
+  workout::{load::Load, prescription::Prescription},
+};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Formatter;
 use termion::{color, cursor, style};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Exercise {
   pub name: String,
   pub description: String,
+  /// Whether the exercise is the one being done. This is only used while a
+  /// workout is running and isn't saved.
+  #[serde(skip)]
   pub selected: bool,
+  #[serde(default, skip_serializing_if = "Prescription::is_timed")]
+  pub prescription: Prescription,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub load: Option<Load>,
+  /// The equipment needed for the exercise, if any.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub equipment: Vec<String>,
 }
 
 impl Exercise {
   pub fn new(name: &str, description: &str) -> Self {
     let name = name.to_owned();
     let description = description.to_owned();
-    Exercise { name, description, selected: false }
+    Exercise {
+      name,
+      description,
+      selected: false,
+      prescription: Prescription::default(),
+      load: None,
+      equipment: vec![],
+    }
+  }
+
+  /// The same exercise with a different [Prescription].
+  pub fn with_prescription(mut self, prescription: Prescription) -> Self {
+    self.prescription = prescription;
+    self
+  }
+
+  /// The same exercise with a prescribed [Load].
+  pub fn with_load(mut self, load: Load) -> Self {
+    self.load = Some(load);
+    self
   }
 
-  pub fn from_vec(v: Vec<String>) -> Self {
-    let mut i = v.into_iter();
-    let name = if let Some(s) = i.next() { s } else { "".to_owned() };
-    let mut description = String::new();
-    loop {
-      if let Some(s) = i.next() {
-        description.push_str(s.as_str());
-      } else {
-        return Self { name, description, selected: false };
-      };
+  /// Whether the exercise can be done with the given equipment.
+  pub fn can_do_with(&self, equipment: &[String]) -> bool {
+    self
+      .equipment
+      .iter()
+      .all(|needed| equipment.iter().any(|e| e.eq_ignore_ascii_case(needed)))
+  }
+
+  /// The prescription and load shown next to the name, if there are any.
+  pub fn details(&self) -> String {
+    let mut details = vec![];
+    if !self.prescription.is_timed() {
+      details.push(self.prescription.to_string());
+    }
+    if let Some(load) = self.load {
+      details.push(format!("@ {}", load));
+    }
+    if details.is_empty() {
+      String::new()
+    } else {
+      format!(" ({})", details.join(" "))
     }
   }
 }
@@ -37,7 +80,7 @@ impl fmt::Display for Exercise {
     write!(
       f,
       "\n\
-      {}{}{}{}{}\n\
+      {}{}{}{}{}{}\n\
       {}{}{}{}\n\
       \n",
       cursor::Left(u16::MAX),
@@ -45,6 +88,7 @@ impl fmt::Display for Exercise {
       color::Fg(color::Red),
       if self.selected { " --> " } else { "     " },
       self.name,
+      self.details(),
       cursor::Left(u16::MAX),
       style::Reset,
       color::Fg(color::Reset),
@@ -53,65 +97,38 @@ impl fmt::Display for Exercise {
   }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ExerciseSet {
-  pub exercises: (Exercise, Exercise, Exercise),
+  /// The exercises done in turn each round, usually three of them.
+  pub exercises: Vec<Exercise>,
   pub exercise_type: ExerciseType,
 }
 
 impl ExerciseSet {
-  pub fn from_vec(v: Vec<Vec<String>>, t: &ExerciseType) -> Self {
-    let mut i = v.into_iter();
-    let e1 = if let Some(s) = i.next() {
-      Exercise::from_vec(s)
-    } else {
-      panic!("Invalid vector sent to Exercise Set");
-    };
-    let e2 = if let Some(s) = i.next() {
-      Exercise::from_vec(s)
-    } else {
-      panic!("Invalid vector sent to Exercise Set");
-    };
-    let e3 = if let Some(s) = i.next() {
-      Exercise::from_vec(s)
-    } else {
-      panic!("Invalid vector sent to Exercise Set");
-    };
-    ExerciseSet { exercises: (e1, e2, e3), exercise_type: *t }
+  /// Iterate over the exercises in the set in order.
+  pub fn iter(&self) -> impl Iterator<Item = &Exercise> {
+    self.exercises.iter()
+  }
+
+  /// The exercise with the given (1 based) id.
+  pub fn get(&self, id: u8) -> Option<&Exercise> {
+    self.iter().nth((id as usize).checked_sub(1)?)
   }
 
-  pub fn select(&mut self, i: u8) {
-    match i {
-      1 => {
-        self.exercises.0.selected = true;
-        self.exercises.1.selected = false;
-        self.exercises.2.selected = false;
-      }
-      2 => {
-        self.exercises.0.selected = false;
-        self.exercises.1.selected = true;
-        self.exercises.2.selected = false;
-      }
-      3 => {
-        self.exercises.0.selected = false;
-        self.exercises.1.selected = false;
-        self.exercises.2.selected = true;
-      }
-      _ => {
-        self.exercises.0.selected = false;
-        self.exercises.1.selected = false;
-        self.exercises.2.selected = false;
-      }
+  /// Select the exercise with the given (1 based) id. Any other id, such as
+  /// 0, leaves none of them selected.
+  pub fn select(&mut self, id: u8) {
+    for (n, exercise) in self.exercises.iter_mut().enumerate() {
+      exercise.selected = n + 1 == id as usize;
     }
   }
 }
 
 impl fmt::Display for ExerciseSet {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-    write!(
-      f,
-      "{}\n{}\n{}\n",
-      self.exercises.0, self.exercises.1, self.exercises.2
-    )
+    for exercise in self.iter() {
+      writeln!(f, "{}", exercise)?;
+    }
+    Ok(())
   }
 }
@@ -0,0 +1,155 @@
+use super::{edit::Edits, Workout};
+use crate::lib::file_format::FileFormat;
+use anyhow::{bail, Result};
+use std::{
+  env,
+  fs::DirBuilder,
+  io::ErrorKind,
+  os::unix::fs::DirBuilderExt,
+  path::{Path, PathBuf},
+  process,
+};
+
+/// The start of each line added to the file to say what was wrong with it.
+/// Lines starting with this are removed before the error is added again.
+const ERROR_PREFIX: &str = "#! ";
+
+/// The editor to open files with: `$VISUAL`, then `$EDITOR`, then `vi`.
+pub fn editor() -> String {
+  env::var("VISUAL")
+    .or_else(|_| env::var("EDITOR"))
+    .ok()
+    .filter(|editor| !editor.trim().is_empty())
+    .unwrap_or_else(|| "vi".to_string())
+}
+
+/// The text of the file with the error added as comments at the top, in
+/// place of the last error if there was one.
+pub fn with_error(text: &str, error: &str) -> String {
+  let mut result = format!("{}Couldn't save this workout:\n", ERROR_PREFIX);
+  for line in error.lines() {
+    result += &format!("{}  {}\n", ERROR_PREFIX, line);
+  }
+  result += &format!(
+    "{}Fix it and save again, or quit without changing anything to give up.\n",
+    ERROR_PREFIX
+  );
+  for line in text.lines().filter(|l| !l.starts_with(ERROR_PREFIX)) {
+    result += line;
+    result += "\n";
+  }
+  result
+}
+
+/// A new directory in the temp directory that only the user can get into,
+/// removed along with everything in it when it's dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+  fn new() -> Result<Self> {
+    for n in 0..100 {
+      let dir =
+        env::temp_dir().join(format!("workout-{}-{}", process::id(), n));
+      // fails rather than using something already there.
+      match DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => return Ok(TempDir(dir)),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+        Err(e) => return Err(e.into()),
+      }
+    }
+    bail!("Couldn't make a directory to edit the workout in")
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+/// Run the editor on a file and wait for it to close.
+fn open(path: &Path) -> Result<()> {
+  // run through the shell so an editor such as `code --wait` works.
+  let status = process::Command::new("sh")
+    .arg("-c")
+    .arg(format!("{} \"$1\"", editor()))
+    .arg("sh")
+    .arg(path)
+    .status()?;
+  if !status.success() {
+    bail!("{} exited with {}", editor(), status);
+  }
+  Ok(())
+}
+
+/// Open a workout as YAML in the user's editor and save it once it's valid.
+/// Whenever it can't be read or saved the editor is opened again with the
+/// error at the top. Closing the editor without changing anything gives up.
+/// Returns whether the workout was saved.
+pub fn edit(workout: &Workout, original: Option<&str>) -> Result<bool> {
+  let edits = Edits::new()?;
+  let name: String = workout
+    .title
+    .chars()
+    .map(|c| if c.is_alphanumeric() { c } else { '-' })
+    .collect();
+  // removed however this returns.
+  let dir = TempDir::new()?;
+  let path = dir.0.join(format!("{}.yml", name));
+  FileFormat::Yaml.write(&path, workout)?;
+
+  loop {
+    let before = std::fs::read_to_string(&path)?;
+    open(&path)?;
+    let after = std::fs::read_to_string(&path)?;
+    if after == before {
+      return Ok(false);
+    }
+
+    // read it the same way as a file in the data directory.
+    let error = match Workout::load_file(&path) {
+      Ok(edited) => match edits.save(&edited, original) {
+        Ok(()) => {
+          println!("Saved {:?}", edited.title);
+          return Ok(true);
+        }
+        Err(e) => e,
+      },
+      Err(e) => e,
+    };
+    std::fs::write(&path, with_error(&after, &format!("{:#}", error)))?;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_replaces_the_last_error() {
+    let text = with_error("title: Workout\n", "missing field `day`");
+    assert!(text.starts_with("#! Couldn't save this workout:\n"));
+    assert!(text.contains("#!   missing field `day`\n"));
+    assert!(text.ends_with("title: Workout\n"));
+
+    let text = with_error(&text, "no sets");
+    assert!(!text.contains("missing field"));
+    assert_eq!(text.matches("Couldn't save").count(), 1);
+    assert!(text.ends_with("title: Workout\n"));
+  }
+
+  #[test]
+  fn it_edits_in_a_private_directory() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = TempDir::new().unwrap();
+    let other = TempDir::new().unwrap();
+    assert_ne!(dir.0, other.0);
+    let mode = dir.0.metadata().unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+
+    let path = dir.0.clone();
+    std::fs::write(path.join("Legs.yml"), "title: Legs\n").unwrap();
+    drop(dir);
+    assert!(!path.exists());
+  }
+}
@@ -0,0 +1,190 @@
+use super::Workout;
+use crate::lib::{
+  enums::{DayOfWeek, ExerciseType},
+  history::{self, Session},
+};
+use chrono::Local;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A question to ask of a workout. Filters can be combined so that every one
+/// has to match (`All`) or any one of them (`Any`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+  Day(DayOfWeek),
+  Type(ExerciseType),
+  /// Workouts that take between `min` and `max` minutes. Exercises done for
+  /// reps without a time cap don't count towards the length.
+  Duration {
+    min: Option<u64>,
+    max: Option<u64>,
+  },
+  /// Workouts that can be done with only this equipment.
+  Equipment(Vec<String>),
+  Tag(String),
+  /// Workouts that haven't been done in this many days, including workouts
+  /// that have never been done.
+  NotDoneIn(i64),
+  /// Workouts with this text in the title, ignoring case.
+  Title(String),
+  All(Vec<Filter>),
+  Any(Vec<Filter>),
+}
+
+impl Filter {
+  /// Check a workout against the filter. The sessions are used to find when
+  /// the workout was last done.
+  pub fn matches(&self, workout: &Workout, sessions: &[Session]) -> bool {
+    match self {
+      Filter::Day(day) => workout.day == *day,
+      Filter::Type(workout_type) => workout.workout_type == *workout_type,
+      Filter::Duration { min, max } => {
+        let mins = workout.duration().as_secs() / 60;
+        min.is_none_or(|min| mins >= min) && max.is_none_or(|max| mins <= max)
+      }
+      Filter::Equipment(equipment) => {
+        workout.exercises().iter().all(|e| e.can_do_with(equipment))
+      }
+      Filter::Tag(tag) => {
+        workout.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+      }
+      Filter::NotDoneIn(days) => {
+        match history::last_done(sessions, &workout.title) {
+          Some(last) => (Local::now() - last).num_days() >= *days,
+          None => true,
+        }
+      }
+      Filter::Title(text) => {
+        workout.title.to_lowercase().contains(&text.to_lowercase())
+      }
+      Filter::All(filters) => {
+        filters.iter().all(|f| f.matches(workout, sessions))
+      }
+      // with nothing to choose from, `Any` matches everything like `All`.
+      Filter::Any(filters) => {
+        filters.is_empty()
+          || filters.iter().any(|f| f.matches(workout, sessions))
+      }
+    }
+  }
+
+  /// A filter matching workouts that match both this filter and `other`.
+  pub fn and(self, other: Filter) -> Self {
+    match self {
+      Filter::All(mut filters) => {
+        filters.push(other);
+        Filter::All(filters)
+      }
+      filter => Filter::All(vec![filter, other]),
+    }
+  }
+
+  /// A filter matching workouts that match either this filter or `other`.
+  pub fn or(self, other: Filter) -> Self {
+    match self {
+      Filter::Any(mut filters) => {
+        filters.push(other);
+        Filter::Any(filters)
+      }
+      filter => Filter::Any(vec![filter, other]),
+    }
+  }
+
+  /// Join a list of filters, shown in parentheses when it's part of another
+  /// list.
+  fn join(
+    f: &mut Formatter<'_>,
+    filters: &[Filter],
+    separator: &str,
+  ) -> fmt::Result {
+    if filters.is_empty() {
+      return write!(f, "Everything");
+    }
+    for (n, filter) in filters.iter().enumerate() {
+      if n > 0 {
+        write!(f, " {} ", separator)?;
+      }
+      match filter {
+        Filter::All(_) | Filter::Any(_) => write!(f, "({})", filter)?,
+        _ => write!(f, "{}", filter)?,
+      }
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Display for Filter {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Filter::Day(day) => write!(f, "{}", day),
+      Filter::Type(workout_type) => write!(f, "{}", workout_type),
+      Filter::Duration { min: Some(min), max: Some(max) } => {
+        write!(f, "{}-{} minutes", min, max)
+      }
+      Filter::Duration { min: Some(min), max: None } => {
+        write!(f, "at least {} minutes", min)
+      }
+      Filter::Duration { min: None, max: Some(max) } => {
+        write!(f, "at most {} minutes", max)
+      }
+      Filter::Duration { min: None, max: None } => write!(f, "any length"),
+      Filter::Equipment(equipment) if equipment.is_empty() => {
+        write!(f, "no equipment")
+      }
+      Filter::Equipment(equipment) => {
+        write!(f, "with {}", equipment.join(", "))
+      }
+      Filter::Tag(tag) => write!(f, "#{}", tag),
+      Filter::NotDoneIn(days) => write!(f, "not done in {} days", days),
+      Filter::Title(text) => write!(f, "{:?}", text),
+      Filter::All(filters) => Filter::join(f, filters, "and"),
+      Filter::Any(filters) => Filter::join(f, filters, "or"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::Duration;
+
+  #[test]
+  fn it_combines_filters() {
+    let workout = Workout {
+      title: "Upper 1".to_string(),
+      day: DayOfWeek::Tuesday,
+      workout_type: ExerciseType::UpperBodyAbs,
+      tags: vec!["Quick".to_string()],
+      ..Workout::default()
+    };
+
+    let upper_on_tuesday = Filter::Type(ExerciseType::UpperBodyAbs)
+      .and(Filter::Day(DayOfWeek::Tuesday))
+      .and(Filter::Tag("quick".to_string()));
+    assert!(upper_on_tuesday.matches(&workout, &[]));
+    assert_eq!(
+      upper_on_tuesday.to_string(),
+      "Upper Body & Abs and Tuesday and #quick"
+    );
+
+    let monday = Filter::Day(DayOfWeek::Monday);
+    assert!(!monday.matches(&workout, &[]));
+    assert!(monday
+      .or(Filter::Title("upper".to_string()))
+      .matches(&workout, &[]));
+    assert!(Filter::Any(vec![]).matches(&workout, &[]));
+    assert_eq!(Filter::Any(vec![]).to_string(), "Everything");
+  }
+
+  #[test]
+  fn it_finds_workouts_not_done_lately() {
+    let workout = Workout::default();
+    let filter = Filter::NotDoneIn(7);
+    assert!(filter.matches(&workout, &[]));
+
+    let recent = Session::new(&workout.title, Local::now() - Duration::days(2));
+    assert!(!filter.matches(&workout, &[recent]));
+    let old = Session::new(&workout.title, Local::now() - Duration::days(9));
+    assert!(filter.matches(&workout, &[old]));
+  }
+}
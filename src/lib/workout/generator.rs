@@ -0,0 +1,143 @@
+use super::{
+  exercise::{Exercise, ExerciseSet},
+  Workout,
+};
+use crate::lib::enums::{DayOfWeek, ExerciseType};
+use anyhow::{bail, Result};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// The constraints used when generating a random workout.
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+  /// Only use exercises from sets of this type.
+  pub workout_type: Option<ExerciseType>,
+  /// The number of sets to generate.
+  pub sets: usize,
+  /// The equipment that is available. Exercises needing anything else are
+  /// left out.
+  pub equipment: Vec<String>,
+  /// The seed for the random number generator. The same seed and options
+  /// always give the same workout.
+  pub seed: u64,
+}
+
+impl Default for GeneratorOptions {
+  fn default() -> Self {
+    GeneratorOptions {
+      workout_type: None,
+      sets: 3,
+      equipment: vec![],
+      seed: rand::random(),
+    }
+  }
+}
+
+/// Every exercise across the workouts along with the type of set it is in,
+/// without repeats.
+pub fn exercise_pool(workouts: &[Workout]) -> Vec<(&Exercise, ExerciseType)> {
+  let mut pool: Vec<(&Exercise, ExerciseType)> = vec![];
+  for set in workouts.iter().flat_map(|w| w.sets.iter()) {
+    for exercise in set.iter() {
+      if !pool.iter().any(|(e, _)| e.name == exercise.name) {
+        pool.push((exercise, set.exercise_type));
+      }
+    }
+  }
+  pool
+}
+
+/// Build a new workout from the exercises in the given workouts. No exercise
+/// is used more than once.
+pub fn generate(
+  workouts: &[Workout],
+  options: &GeneratorOptions,
+) -> Result<Workout> {
+  let mut pool: Vec<_> = exercise_pool(workouts)
+    .into_iter()
+    .filter(|(_, t)| options.workout_type.is_none_or(|wt| wt == *t))
+    .filter(|(e, _)| e.can_do_with(&options.equipment))
+    .collect();
+
+  let needed = options.sets * 3;
+  if options.sets == 0 {
+    bail!("A workout needs at least one set");
+  }
+  if pool.len() < needed {
+    bail!(
+      "Only {} exercises match - {} are needed for {} sets",
+      pool.len(),
+      needed,
+      options.sets
+    );
+  }
+
+  let mut rng = StdRng::seed_from_u64(options.seed);
+  pool.shuffle(&mut rng);
+
+  let sets: Vec<ExerciseSet> = pool[..needed]
+    .chunks(3)
+    .map(|chunk| {
+      let exercises = chunk
+        .iter()
+        .map(|(e, _)| {
+          let mut e = (*e).clone();
+          e.selected = false;
+          e
+        })
+        .collect();
+      ExerciseSet {
+        exercises,
+        exercise_type: options.workout_type.unwrap_or(chunk[0].1),
+      }
+    })
+    .collect();
+
+  let workout_type = options.workout_type.unwrap_or(sets[0].exercise_type);
+  Ok(Workout::new(
+    &format!("Random Workout {}", options.seed),
+    None,
+    DayOfWeek::today(),
+    workout_type,
+    sets,
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn workouts() -> Vec<Workout> {
+    let mut workout = Workout::default();
+    for (s, set) in workout.sets.iter_mut().enumerate() {
+      set.exercises[0].name = format!("Squats {}", s);
+      set.exercises[1].name = format!("Lunges {}", s);
+      set.exercises[2].name = format!("Curls {}", s);
+      set.exercises[2].equipment = vec!["Dumbbells".to_string()];
+    }
+    vec![workout]
+  }
+
+  #[test]
+  fn it_generates_the_same_workout_from_the_same_seed() {
+    let workouts = workouts();
+    let options = GeneratorOptions { sets: 2, seed: 7, ..Default::default() };
+    let a = generate(&workouts, &options).unwrap();
+    let b = generate(&workouts, &options).unwrap();
+    let names = |w: &Workout| -> Vec<String> {
+      w.exercises().iter().map(|e| e.name.clone()).collect()
+    };
+    assert_eq!(names(&a), names(&b));
+    assert_eq!(names(&a).len(), 6);
+  }
+
+  #[test]
+  fn it_leaves_out_exercises_without_the_equipment() {
+    let workouts = workouts();
+    let options = GeneratorOptions { sets: 2, seed: 1, ..Default::default() };
+    let workout = generate(&workouts, &options).unwrap();
+    assert!(workout.exercises().iter().all(|e| !e.name.starts_with("Curls")));
+
+    let options = GeneratorOptions { sets: 3, seed: 1, ..Default::default() };
+    assert!(generate(&workouts, &options).is_err());
+  }
+}
@@ -0,0 +1,229 @@
+use super::{
+  exercise::{Exercise, ExerciseSet},
+  migrate, Workout,
+};
+use crate::lib::{
+  enums::{DayOfWeek, ExerciseType},
+  file_format::FileFormat,
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+fn default_warmup_length() -> u64 {
+  5
+}
+
+/// A workout written by hand to be imported. Sets are lists of exercises:
+///
+/// ```yaml
+/// sets:
+///   - set:
+///       - exercise: Body Weight Squats
+///         description: Feet shoulder width apart.
+///       - Glute Bridge Marches | Sit-up position - marching with your feet.
+///       - Ab Bicycles
+/// ```
+///
+/// The nested lists used before, where the number of dashes decided what each
+/// line meant, can still be read.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkoutImport {
+  pub title: String,
+  #[serde(default)]
+  pub link: String,
+  pub day: DayOfWeek,
+  #[serde(default = "default_warmup_length")]
+  pub warmup_length: u64,
+  pub workout_type: ExerciseType,
+  pub sets: Vec<ImportSet>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+}
+
+/// A set in an import file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ImportSet {
+  Set {
+    set: Vec<ImportExercise>,
+  },
+  /// The old nested lists. Each exercise is a list starting with its name
+  /// followed by the lines of its description.
+  Lists(Vec<Vec<String>>),
+}
+
+/// An exercise in an import file, either as `Name | Description` or with an
+/// `exercise:` and a `description:`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ImportExercise {
+  Compact(String),
+  Full {
+    exercise: String,
+    #[serde(default)]
+    description: String,
+  },
+}
+
+impl ImportExercise {
+  /// The name and description of the exercise.
+  fn parts(&self) -> (String, String) {
+    match self {
+      ImportExercise::Compact(text) => match text.split_once('|') {
+        Some((name, description)) => {
+          (name.trim().to_string(), description.trim().to_string())
+        }
+        None => (text.trim().to_string(), String::new()),
+      },
+      ImportExercise::Full { exercise, description } => {
+        (exercise.trim().to_string(), description.trim().to_string())
+      }
+    }
+  }
+}
+
+impl ImportSet {
+  /// The set in the new form, joining the description lines of the old
+  /// lists with spaces.
+  pub fn convert(&self) -> Self {
+    ImportSet::Set { set: self.new_form() }
+  }
+
+  /// The exercises of the set as they're written in the new form.
+  fn new_form(&self) -> Vec<ImportExercise> {
+    match self {
+      ImportSet::Set { set } => set.clone(),
+      ImportSet::Lists(lists) => lists
+        .iter()
+        .map(|list| ImportExercise::Full {
+          exercise: list.first().cloned().unwrap_or_default(),
+          description: list
+            .iter()
+            .skip(1)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" "),
+        })
+        .collect(),
+    }
+  }
+
+  /// The name and description of each exercise.
+  fn exercises(&self) -> Vec<(String, String)> {
+    self.new_form().iter().map(|e| e.parts()).collect()
+  }
+}
+
+impl WorkoutImport {
+  /// Load a single yaml, json or toml file as a workout.
+  pub fn load_file(filename: &Path) -> Result<Self> {
+    FileFormat::read(filename)
+  }
+
+  /// Whether any set still uses the old nested lists.
+  pub fn is_old_form(&self) -> bool {
+    self.sets.iter().any(|s| matches!(s, ImportSet::Lists(_)))
+  }
+
+  /// The same workout with every set in the new form.
+  pub fn convert(self) -> Self {
+    Self { sets: self.sets.iter().map(|s| s.convert()).collect(), ..self }
+  }
+
+  pub fn upgrade(self) -> Result<Workout> {
+    let sets = self
+      .sets
+      .iter()
+      .map(|set| ExerciseSet {
+        exercises: set
+          .exercises()
+          .into_iter()
+          .map(|(name, description)| Exercise::new(&name, &description))
+          .collect(),
+        exercise_type: self.workout_type,
+      })
+      .collect();
+
+    let workout = Workout {
+      schema_version: migrate::CURRENT_VERSION,
+      title: self.title,
+      link: self.link,
+      day: self.day,
+      warmup_length: self.warmup_length,
+      workout_type: self.workout_type,
+      sets,
+      tags: self.tags,
+    };
+    workout.validate()?;
+    Ok(workout)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NEW: &str = "
+title: New
+day: Monday
+workout_type: LowerBodyAbs
+sets:
+  - set:
+      - exercise: Squats
+        description: Feet shoulder width apart.
+      - Lunges | Step forward, one leg at a time.
+      - Crunches
+";
+
+  const OLD: &str = "
+title: Old
+link: ''
+day: Monday
+warmup_length: 5
+workout_type: LowerBodyAbs
+sets:
+  - - - Squats
+      - Feet shoulder
+      - width apart.
+    - - Lunges
+      - Step forward, one leg at a time.
+    - - Crunches
+";
+
+  #[test]
+  fn it_reads_both_forms() {
+    let new: WorkoutImport = serde_yaml::from_str(NEW).unwrap();
+    let old: WorkoutImport = serde_yaml::from_str(OLD).unwrap();
+    assert!(!new.is_old_form());
+    assert!(old.is_old_form());
+
+    let new = new.upgrade().unwrap();
+    let old = old.upgrade().unwrap();
+    for workout in [&new, &old] {
+      let set = &workout.sets[0];
+      assert_eq!(set.exercises[0].name, "Squats");
+      assert_eq!(set.exercises[0].description, "Feet shoulder width apart.");
+      assert_eq!(
+        set.exercises[1].description,
+        "Step forward, one leg at a time."
+      );
+      assert_eq!(set.exercises[2].description, "");
+    }
+    assert_eq!(new.warmup_length, 5);
+  }
+
+  #[test]
+  fn it_converts_old_files() {
+    let old: WorkoutImport = serde_yaml::from_str(OLD).unwrap();
+    let converted = old.convert();
+    assert!(!converted.is_old_form());
+    let text = serde_yaml::to_string(&converted).unwrap();
+    assert!(text.contains("exercise: Squats"));
+    assert!(text.contains("description: Feet shoulder width apart."));
+
+    let mut empty: WorkoutImport = serde_yaml::from_str(NEW).unwrap();
+    empty.sets = vec![ImportSet::Set { set: vec![] }];
+    assert!(empty.upgrade().is_err());
+  }
+}
@@ -0,0 +1,352 @@
+use super::{Workout, WorkoutImport};
+use crate::lib::{file_format::FileFormat, settings::Settings};
+use anyhow::Result;
+use serde_yaml::{from_reader, to_writer};
+use std::{
+  fmt,
+  fmt::Formatter,
+  fs::File,
+  path::{Path, PathBuf},
+  str::FromStr,
+};
+use workout_paths::*;
+
+/// The file in the state directory listing the hashes of imported files.
+pub const LOG_FILE: &str = "imports.yml";
+
+/// What to do when an imported workout has the same title as one that's
+/// already saved.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Conflict {
+  /// Replace the saved workout.
+  Overwrite,
+  /// Save the import under a new title, such as `Workout 1.1 (2)`.
+  Rename,
+  /// Keep the saved workout and leave the import out.
+  Skip,
+}
+
+const OVERWRITE: &str = "overwrite";
+const RENAME: &str = "rename";
+const SKIP: &str = "skip";
+
+impl FromStr for Conflict {
+  type Err = ();
+
+  fn from_str(str: &str) -> Result<Self, ()> {
+    match str.to_lowercase().as_str() {
+      OVERWRITE | "o" => Ok(Conflict::Overwrite),
+      RENAME | "r" => Ok(Conflict::Rename),
+      SKIP | "s" => Ok(Conflict::Skip),
+      _ => Err(()),
+    }
+  }
+}
+
+impl fmt::Display for Conflict {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Conflict::Overwrite => OVERWRITE,
+        Conflict::Rename => RENAME,
+        Conflict::Skip => SKIP,
+      }
+    )
+  }
+}
+
+/// The 64 bit FNV-1a hash of some bytes, used to tell whether a file has been
+/// imported before.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in bytes {
+    hash ^= *byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  hash
+}
+
+/// What happened to each file in an import.
+#[derive(Debug, Default)]
+pub struct Report {
+  /// The titles the workouts were saved under.
+  pub imported: Vec<String>,
+  /// Files that were imported before and haven't changed since.
+  pub unchanged: Vec<PathBuf>,
+  /// Workouts left out because one with the same title was kept.
+  pub skipped: Vec<String>,
+  /// Files that couldn't be imported along with why.
+  pub failed: Vec<(PathBuf, String)>,
+}
+
+impl Report {
+  pub fn is_empty(&self) -> bool {
+    self.imported.is_empty()
+      && self.unchanged.is_empty()
+      && self.skipped.is_empty()
+      && self.failed.is_empty()
+  }
+}
+
+impl fmt::Display for Report {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    for title in self.imported.iter() {
+      writeln!(f, "Imported {:?}", title)?;
+    }
+    for path in self.unchanged.iter() {
+      writeln!(f, "Skipped {:?} - it was imported before", path)?;
+    }
+    for title in self.skipped.iter() {
+      writeln!(f, "Skipped {:?} - a workout with that title exists", title)?;
+    }
+    for (path, e) in self.failed.iter() {
+      writeln!(f, "Couldn't import {:?}: {}", path, e)?;
+    }
+    Ok(())
+  }
+}
+
+/// Imports workout files into the data directory. Files from the import
+/// directory are moved to `processed` once they're imported, or to `failed`
+/// with a `.error.txt` report next to them when they can't be.
+pub struct Importer {
+  pub import_dir: PathBuf,
+  pub processed_dir: PathBuf,
+  pub failed_dir: PathBuf,
+  pub data_dir: PathBuf,
+  /// Where the hashes of imported files are kept.
+  pub log: PathBuf,
+  pub format: FileFormat,
+}
+
+impl Importer {
+  pub fn new() -> Result<Self> {
+    Ok(Importer {
+      import_dir: import_path(),
+      processed_dir: processed_path(),
+      failed_dir: failed_path(),
+      data_dir: data_path(),
+      log: state_path().join(LOG_FILE),
+      format: Settings::load()?.save_format,
+    })
+  }
+
+  /// The files waiting in the import directory.
+  pub fn pending(&self) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(&self.import_dir) {
+      Ok(entries) => entries,
+      Err(_) => return Ok(vec![]),
+    };
+    let mut paths = vec![];
+    for entry in entries {
+      let path = entry?.path();
+      if path.is_file() {
+        paths.push(path);
+      }
+    }
+    paths.sort();
+    Ok(paths)
+  }
+
+  fn load_log(&self) -> Result<Vec<String>> {
+    if !self.log.exists() {
+      return Ok(vec![]);
+    }
+    Ok(from_reader(File::open(&self.log)?)?)
+  }
+
+  fn save_log(&self, hashes: &[String]) -> Result<()> {
+    if let Some(dir) = self.log.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+    to_writer(File::create(&self.log)?, hashes)?;
+    Ok(())
+  }
+
+  /// Move a file out of the import directory, returning where it went. Files
+  /// given from anywhere else are left where they are.
+  fn archive(&self, path: &Path, dir: &Path) -> Result<Option<PathBuf>> {
+    if path.parent() != Some(self.import_dir.as_path()) {
+      return Ok(None);
+    }
+    std::fs::create_dir_all(dir)?;
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+    let mut to = dir.join(&name);
+    let mut n = 1;
+    while to.exists() {
+      n += 1;
+      to = dir.join(format!("{}.{}", n, name));
+    }
+    std::fs::rename(path, &to)?;
+    Ok(Some(to))
+  }
+
+  /// Record why a file couldn't be imported and move it to `failed`.
+  fn fail(&self, path: &Path, error: String, report: &mut Report) {
+    let moved = self.archive(path, &self.failed_dir).and_then(|to| {
+      if let Some(to) = to {
+        let mut report_path = to.into_os_string();
+        report_path.push(".error.txt");
+        std::fs::write(report_path, format!("{}\n", error))?;
+      }
+      Ok(())
+    });
+    let error = match moved {
+      Ok(()) => error,
+      Err(e) => {
+        format!("{} (and it couldn't be moved to failed: {})", error, e)
+      }
+    };
+    report.failed.push((path.to_path_buf(), error));
+  }
+
+  /// Import the files, asking `decide` what to do whenever a title is taken.
+  /// Files that only hold comments, like the example, are left alone. A file
+  /// that can't be imported is reported and the rest are carried on with.
+  pub fn run(
+    &self,
+    paths: &[PathBuf],
+    mut decide: impl FnMut(&str) -> Result<Conflict>,
+  ) -> Result<Report> {
+    let mut report = Report::default();
+    let mut hashes = self.load_log()?;
+    let mut titles: Vec<String> = match Workout::load_dir(&self.data_dir) {
+      Ok((workouts, _)) => workouts.into_iter().map(|w| w.title).collect(),
+      Err(_) => vec![],
+    };
+
+    for path in paths {
+      if let Err(e) =
+        self.import(path, &mut hashes, &mut titles, &mut decide, &mut report)
+      {
+        self.fail(path, e.to_string(), &mut report);
+      }
+    }
+
+    self.save_log(&hashes)?;
+    Ok(report)
+  }
+
+  /// Import a single file, adding its hash once it's been dealt with.
+  fn import(
+    &self,
+    path: &Path,
+    hashes: &mut Vec<String>,
+    titles: &mut Vec<String>,
+    decide: &mut impl FnMut(&str) -> Result<Conflict>,
+    report: &mut Report,
+  ) -> Result<()> {
+    let bytes = std::fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    if text.lines().all(|l| l.trim().is_empty() || l.trim().starts_with('#')) {
+      return Ok(());
+    }
+    let hash = format!("{:016x}", fnv1a(&bytes));
+    if hashes.contains(&hash) {
+      self.archive(path, &self.processed_dir)?;
+      report.unchanged.push(path.to_path_buf());
+      return Ok(());
+    }
+
+    let mut workout = WorkoutImport::load_file(path)?.upgrade()?;
+    if titles.contains(&workout.title) {
+      match decide(&workout.title)? {
+        Conflict::Overwrite => {}
+        Conflict::Rename => {
+          let mut n = 2;
+          while titles.contains(&format!("{} ({})", workout.title, n)) {
+            n += 1;
+          }
+          workout.title = format!("{} ({})", workout.title, n);
+        }
+        Conflict::Skip => {
+          report.skipped.push(workout.title);
+          hashes.push(hash);
+          self.archive(path, &self.processed_dir)?;
+          return Ok(());
+        }
+      }
+    }
+
+    workout.save_to(&self.data_dir, self.format)?;
+    titles.push(workout.title.clone());
+    report.imported.push(workout.title);
+    hashes.push(hash);
+    self.archive(path, &self.processed_dir)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lib::util::test_dir;
+
+  const WORKOUT: &str = "
+title: Imported
+day: Monday
+workout_type: LowerBodyAbs
+sets:
+  - set: [Squats, Lunges, Crunches]
+";
+
+  fn importer(name: &str) -> Importer {
+    let root = test_dir(&format!("importer-{}", name));
+    let import_dir = root.join("import");
+    std::fs::create_dir_all(&import_dir).unwrap();
+    Importer {
+      processed_dir: import_dir.join("processed"),
+      failed_dir: import_dir.join("failed"),
+      import_dir,
+      data_dir: root.join("data"),
+      log: root.join("state").join(LOG_FILE),
+      format: FileFormat::Yaml,
+    }
+  }
+
+  #[test]
+  fn it_archives_imports_and_skips_unchanged_files() {
+    let importer = importer("archive");
+    let good = importer.import_dir.join("good.yml");
+    let bad = importer.import_dir.join("bad.yml");
+    std::fs::write(&good, WORKOUT).unwrap();
+    std::fs::write(&bad, "title: [nope").unwrap();
+
+    let report = importer
+      .run(&importer.pending().unwrap(), |_| Ok(Conflict::Skip))
+      .unwrap();
+    assert_eq!(report.imported, vec!["Imported".to_string()]);
+    assert_eq!(report.failed.len(), 1);
+    assert!(importer.processed_dir.join("good.yml").exists());
+    assert!(importer.failed_dir.join("bad.yml.error.txt").exists());
+    assert!(importer.pending().unwrap().is_empty());
+
+    std::fs::write(&good, WORKOUT).unwrap();
+    let report = importer
+      .run(&importer.pending().unwrap(), |_| Ok(Conflict::Skip))
+      .unwrap();
+    assert_eq!(report.unchanged, vec![good]);
+    assert!(importer.processed_dir.join("2.good.yml").exists());
+  }
+
+  #[test]
+  fn it_renames_workouts_with_taken_titles() {
+    let importer = importer("rename");
+    let path = importer.import_dir.join("first.yml");
+    std::fs::write(&path, WORKOUT).unwrap();
+    let missing = importer.data_dir.join("missing.yml");
+    let report = importer
+      .run(&[missing.clone(), path.clone()], |_| Ok(Conflict::Skip))
+      .unwrap();
+    assert_eq!(report.failed[0].0, missing);
+    assert_eq!(report.imported, vec!["Imported".to_string()]);
+    assert!(importer.log.exists());
+
+    std::fs::write(&path, format!("{}tags: [changed]\n", WORKOUT)).unwrap();
+    let report = importer.run(&[path], |_| Ok(Conflict::Rename)).unwrap();
+    assert_eq!(report.imported, vec!["Imported (2)".to_string()]);
+    assert!(importer.data_dir.join("Imported (2).yml").exists());
+  }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
+pub enum WeightUnit {
+  Kg,
+  Lb,
+}
+
+const KG: &str = "kg";
+const LB: &str = "lb";
+
+impl fmt::Display for WeightUnit {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        WeightUnit::Kg => KG,
+        WeightUnit::Lb => LB,
+      }
+    )
+  }
+}
+
+impl FromStr for WeightUnit {
+  type Err = ();
+
+  fn from_str(str: &str) -> Result<Self, ()> {
+    match str.to_lowercase().as_str() {
+      KG | "kgs" => Ok(WeightUnit::Kg),
+      LB | "lbs" => Ok(WeightUnit::Lb),
+      &_ => Err(()),
+    }
+  }
+}
+
+/// A weight lifted during an exercise.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
+pub struct Load {
+  pub weight: f32,
+  pub unit: WeightUnit,
+}
+
+impl Load {
+  pub fn new(weight: f32, unit: WeightUnit) -> Self {
+    Load { weight, unit }
+  }
+
+  /// Parse a load such as `16`, `16kg` or `35 lb`. A bare number uses the
+  /// given unit.
+  pub fn parse(str: &str, default_unit: WeightUnit) -> Option<Self> {
+    let str = str.trim();
+    let split = str
+      .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+      .unwrap_or(str.len());
+    let (weight, unit) = str.split_at(split);
+    let weight = weight.parse().ok()?;
+    let unit = match unit.trim() {
+      "" => default_unit,
+      unit => WeightUnit::from_str(unit).ok()?,
+    };
+    Some(Load::new(weight, unit))
+  }
+}
+
+impl fmt::Display for Load {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{} {}", self.weight, self.unit)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_parses_loads_with_and_without_units() {
+    assert_eq!(
+      Load::parse("16", WeightUnit::Kg),
+      Some(Load::new(16.0, WeightUnit::Kg))
+    );
+    assert_eq!(
+      Load::parse("35 lb", WeightUnit::Kg),
+      Some(Load::new(35.0, WeightUnit::Lb))
+    );
+    assert_eq!(
+      Load::parse("17.5kg", WeightUnit::Lb),
+      Some(Load::new(17.5, WeightUnit::Kg))
+    );
+    assert_eq!(Load::parse("heavy", WeightUnit::Kg), None);
+  }
+}
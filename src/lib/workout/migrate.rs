@@ -0,0 +1,108 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+/// The version of the workout file structure written by this version of the
+/// program.
+pub const CURRENT_VERSION: u64 = 1;
+
+/// Upgrades a workout from one version to the next. The migration at index
+/// `n` upgrades a version `n` workout to version `n + 1`.
+type Migration = fn(&mut Value);
+
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [remove_selected];
+
+/// Version 0 files have no version and save the `selected` flag used while a
+/// workout is running on every exercise.
+fn remove_selected(workout: &mut Value) {
+  let sets = workout.get_mut("sets").and_then(Value::as_array_mut);
+  for set in sets.into_iter().flatten() {
+    let exercises = set.get_mut("exercises").and_then(Value::as_array_mut);
+    for exercise in exercises.into_iter().flatten() {
+      if let Some(exercise) = exercise.as_object_mut() {
+        exercise.remove("selected");
+      }
+    }
+  }
+}
+
+/// The version a workout was saved with. Files without a version are from
+/// before versions were added.
+pub fn version(workout: &Value) -> u64 {
+  workout.get("schema_version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Upgrade a workout to the current version, returning whether anything
+/// changed.
+pub fn migrate(workout: &mut Value) -> Result<bool> {
+  let from = version(workout);
+  if from > CURRENT_VERSION {
+    bail!(
+      "it was saved with version {} but this program only reads up to {}",
+      from,
+      CURRENT_VERSION
+    );
+  }
+  for migration in MIGRATIONS.iter().skip(from as usize) {
+    migration(workout);
+  }
+  if let Some(workout) = workout.as_object_mut() {
+    workout.insert("schema_version".to_string(), CURRENT_VERSION.into());
+  }
+  Ok(from < CURRENT_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lib::util::test_dir;
+  use crate::lib::workout::Workout;
+
+  /// A workout from before versions were added.
+  const OLD: &str = "
+title: Old
+link: ''
+day: Monday
+warmup_length: 5
+workout_type: LowerBodyAbs
+sets:
+  - exercises:
+      - {name: Squats, description: Squat, selected: false}
+      - {name: Lunges, description: Lunge, selected: true}
+      - {name: Crunches, description: Crunch, selected: false}
+    exercise_type: LowerBodyAbs
+";
+
+  #[test]
+  fn it_upgrades_unversioned_workouts() {
+    let mut value: Value = serde_yaml::from_str(OLD).unwrap();
+
+    assert!(migrate(&mut value).unwrap());
+    assert!(!value.to_string().contains("selected"));
+    let workout: Workout = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(workout.schema_version, CURRENT_VERSION);
+    assert!(!migrate(&mut value).unwrap());
+
+    value["schema_version"] = (CURRENT_VERSION + 1).into();
+    assert!(migrate(&mut value).is_err());
+  }
+
+  #[test]
+  fn it_only_rewrites_files_when_upgrading() {
+    let dir = test_dir("migrate");
+    let path = dir.join("Old.yml");
+    std::fs::write(&path, OLD).unwrap();
+
+    let (workouts, errors) = Workout::load_dir(&dir).unwrap();
+    assert_eq!(workouts[0].schema_version, CURRENT_VERSION);
+    assert!(errors.is_empty());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), OLD);
+
+    let (upgraded, errors) = Workout::upgrade_dir(&dir).unwrap();
+    assert_eq!((upgraded, errors.len()), (vec![path.clone()], 0));
+    assert_eq!(Workout::load_file(&path).unwrap().title, "Old");
+    assert!(!std::fs::read_to_string(&path).unwrap().contains("selected"));
+    assert!(Workout::upgrade_dir(&dir).unwrap().0.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -1,120 +1,82 @@
 use crate::{
+  lib::history::{Outcome, RunStats},
   lib::util::pause,
   lib::workout::exercise::{Exercise, ExerciseSet},
   lib::{
+    control::{Control, Live, Remote},
     enums::{DayOfWeek, ExerciseType},
+    events::{Event, Events},
+    file_format::FileFormat,
+    hooks::Hooks,
     screen::{Screen, ScreenType},
+    settings::Settings,
     timer::Timer,
-    util::{clear_screen, just_left},
+    util::{clear_screen, just_left, KeyReader},
   },
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Error, Result};
+use checkpoint::Checkpoint;
+use chrono::Local;
 use rusty_audio::Audio;
 use serde::{Deserialize, Serialize};
-use serde_yaml::{from_reader, to_writer};
 
 use std::{
-  fs::File,
-  io::{stdin, stdout, Write},
-  path::Path,
-  sync::mpsc,
-  thread::{sleep, spawn},
+  io::{stdout, Write},
+  path::{Path, PathBuf},
+  thread::sleep,
   time::Duration,
 };
-use termion::{event::Key, input::TermRead, raw::IntoRawMode, style};
+use termion::{raw::IntoRawMode, style};
 use workout_paths::*;
 
+pub mod checkpoint;
+pub mod edit;
 pub mod exercise;
+pub mod external;
+pub mod filter;
+pub mod generator;
+pub mod import;
+pub mod importer;
+pub mod load;
+pub mod migrate;
+pub mod prescription;
+pub mod preview;
+pub mod search;
 pub mod workout_list;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct WorkoutImport {
-  pub title: String,
-  pub link: String,
-  pub day: DayOfWeek,
-  pub warmup_length: u64,
-  pub workout_type: ExerciseType,
-  pub sets: Vec<Vec<Vec<String>>>,
-}
-
-impl WorkoutImport {
-  /// Load a single yaml file as a workout.
-  pub fn load_file(filename: &Path) -> Result<Self> {
-    let f = File::open(filename)?;
-    let result: WorkoutImport = from_reader(f)?;
-    Ok(result.compress())
-  }
+pub use import::WorkoutImport;
 
-  fn compress(mut self) -> Self {
-    let mut sets = vec![];
-    for i in 0..self.sets.len() {
-      let mut set = vec![];
-      let old_set = &self.sets[i];
-      for old_exercise in old_set.iter() {
-        let (head, tail) = old_exercise.split_at(1);
-        let head = &head[0];
-        let exercise = vec![String::from(head), tail.join(" ")];
-        set.push(exercise);
-      }
-      sets.push(set);
-    }
-    self.sets = sets;
-    self
-  }
-
-  pub fn upgrade(self) -> Workout {
-    let mut sets = vec![];
-    for set in self.sets {
-      sets.push(ExerciseSet::from_vec(set, &self.workout_type));
-    }
-    Workout {
-      title: self.title,
-      link: self.link,
-      day: self.day,
-      warmup_length: self.warmup_length,
-      workout_type: self.workout_type,
-      sets,
-    }
-  }
+/// Where to start running a workout from.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Start {
+  /// A (0 based) screen index.
+  Screen(usize),
+  /// A (0 based) set index. The set starts with the warmup or rest before it.
+  Set(usize),
+}
 
-  /// Load everything
-  pub fn load_all() -> Result<Vec<Self>> {
-    println!("Loading Workouts from {:?}", import_path());
-    let paths = match std::fs::read_dir(import_path()) {
-      Ok(p) => p,
-      Err(_) => {
-        return Ok(vec![]);
-      }
-    };
-    let mut paths = paths
-      .map(|res| res.map(|e| e.path()))
-      .collect::<Result<Vec<_>, std::io::Error>>()?;
-    paths.sort();
-    let mut workouts = vec![];
-    for path in paths {
-      match Self::load_file(&path) {
-        Ok(workout) => {
-          workouts.push(workout);
-        }
-        Err(e) => {
-          println!("Error importing workout {:?}", e);
-          pause()?;
-        }
-      }
-    }
+/// Files that failed to load along with the reason why.
+pub type LoadErrors = Vec<(PathBuf, Error)>;
 
-    Ok(workouts)
-  }
-}
+/// Workouts read from files, with whether each had to be upgraded.
+type Loaded = Vec<(PathBuf, Workout, bool)>;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Workout {
+  /// The version of the file structure the workout was saved with. Older
+  /// files are upgraded when they're loaded.
+  #[serde(default)]
+  pub schema_version: u64,
   pub title: String,
   pub link: String,
   pub day: DayOfWeek,
+  /// The length of the warmup in minutes.
   pub warmup_length: u64,
   pub workout_type: ExerciseType,
   pub sets: Vec<ExerciseSet>,
+  /// Free form labels such as "quick" or "no jumping" to filter by.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
 }
 
 impl Workout {
@@ -127,89 +89,256 @@ impl Workout {
     sets: Vec<ExerciseSet>,
   ) -> Self {
     Workout {
+      schema_version: migrate::CURRENT_VERSION,
       title: title.to_string(),
       link: if let Some(link) = link { link } else { "".to_string() },
       day,
-      warmup_length: 60 * 5,
+      warmup_length: 5,
       workout_type,
       sets,
+      tags: vec![],
     }
   }
 
-  /// Load a single yaml file as a workout.
+  /// Load a single yaml, json or toml file as a workout, upgrading it if it
+  /// was saved by an older version.
   pub fn load_file(filename: &Path) -> Result<Self> {
-    let f = File::open(filename)?;
-    let result: Workout = from_reader(f)?;
-    Ok(result)
+    Ok(Self::read(filename)?.0)
+  }
+
+  /// Load a workout along with whether it had to be upgraded.
+  fn read(filename: &Path) -> Result<(Self, bool)> {
+    let mut value: serde_json::Value = FileFormat::read(filename)?;
+    let migrated = migrate::migrate(&mut value)
+      .map_err(|e| anyhow!("Can't read {:?}: {}", filename, e))?;
+    Ok((serde_json::from_value(value)?, migrated))
   }
 
   /// Load everything
   pub fn load_all() -> Result<Vec<Self>> {
     println!("Loading Workouts from {:?}", data_path());
-    let paths = std::fs::read_dir(data_path())?
+    let (workouts, errors) = Self::load_dir(&data_path())?;
+    for (_, e) in errors {
+      println!("Error loading workout file {:?}", e);
+      pause()?;
+    }
+    pause()?;
+
+    Ok(workouts)
+  }
+
+  /// Load every workout in a directory without stopping to report errors.
+  /// Files that fail to load are returned alongside the workouts. Workouts
+  /// saved by an older version are upgraded, but the files are left alone
+  /// until they're saved, see [Workout::upgrade_dir].
+  pub fn load_dir(dir: &Path) -> Result<(Vec<Self>, LoadErrors)> {
+    let (loaded, errors) = Self::read_dir(dir)?;
+    Ok((loaded.into_iter().map(|(_, workout, _)| workout).collect(), errors))
+  }
+
+  /// Save every workout in a directory that was saved by an older version
+  /// again in the current structure, returning the files that were upgraded.
+  pub fn upgrade_dir(dir: &Path) -> Result<(Vec<PathBuf>, LoadErrors)> {
+    let (loaded, mut errors) = Self::read_dir(dir)?;
+    let mut upgraded = vec![];
+    for (path, workout, migrated) in loaded {
+      if !migrated {
+        continue;
+      }
+      match FileFormat::from_path(&path)
+        .and_then(|format| format.write(&path, &workout))
+      {
+        Ok(()) => upgraded.push(path),
+        Err(e) => errors.push((path, e)),
+      }
+    }
+    Ok((upgraded, errors))
+  }
+
+  /// Read every file in a directory, with whether each had to be upgraded.
+  fn read_dir(dir: &Path) -> Result<(Loaded, LoadErrors)> {
+    let mut paths = std::fs::read_dir(dir)?
       .map(|res| res.map(|e| e.path()))
       .collect::<Result<Vec<_>, std::io::Error>>()?;
-    let mut workouts = vec![];
-    for path in paths {
-      match Self::load_file(&path) {
-        Ok(workout) => {
-          workouts.push(workout);
-        }
-        Err(e) => {
-          println!("Error loading workout file {:?}", e);
-          pause()?;
-        }
+    paths.sort();
+    let mut loaded = vec![];
+    let mut errors = vec![];
+    for path in paths.into_iter().filter(|p| p.is_file()) {
+      match Self::read(&path) {
+        Ok((workout, migrated)) => loaded.push((path, workout, migrated)),
+        Err(e) => errors.push((path, e)),
       }
     }
-    pause()?;
 
-    Ok(workouts)
+    Ok((loaded, errors))
   }
 
+  /// Check the workout can be saved and run: it needs a title that works as
+  /// a file name, at least one set and at least one named exercise in each,
+  /// and no reps or seconds of 0.
+  pub fn validate(&self) -> Result<()> {
+    if self.title.trim().is_empty() {
+      bail!("The workout needs a title");
+    }
+    if self.title.contains(['/', '\\']) {
+      bail!("{:?} can't have a / or \\ in it", self.title);
+    }
+    if self.sets.is_empty() {
+      bail!("{:?} has no sets", self.title);
+    }
+    for (n, set) in self.sets.iter().enumerate() {
+      if set.exercises.is_empty() {
+        bail!("Set {} of {:?} has no exercises", n + 1, self.title);
+      }
+      if set.iter().any(|e| e.name.trim().is_empty()) {
+        bail!("Set {} of {:?} has an exercise with no name", n + 1, self.title);
+      }
+      if let Some(e) = set.iter().find(|e| e.prescription.has_zero()) {
+        bail!(
+          "{:?} in set {} of {:?} is {} - it needs to be more than 0",
+          e.name,
+          n + 1,
+          self.title,
+          e.prescription
+        );
+      }
+    }
+    Ok(())
+  }
+
+  /// Save the workout to the data directory in the format from the settings,
+  /// replacing any workout with the same title.
   pub fn save(&self) -> Result<()> {
-    if std::fs::read_dir(DATA_DIR).is_err() {
-      std::fs::create_dir(DATA_DIR)?;
+    self.save_as(Settings::load()?.save_format)
+  }
+
+  /// Save the workout to the data directory in the given format, replacing
+  /// any workout with the same title whatever format it was in.
+  pub fn save_as(&self, format: FileFormat) -> Result<()> {
+    self.save_to(&data_path(), format)
+  }
+
+  /// Save the workout to a directory in the given format, replacing any
+  /// workout with the same title whatever format it was in. The old files are
+  /// only removed once the new one is in place.
+  pub fn save_to(&self, dir: &Path, format: FileFormat) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.{}", self.title, format));
+    // written alongside first so a failed write leaves the old file alone.
+    let temp = dir.join(format!(".{}.{}.tmp", self.title, format));
+    if let Err(e) =
+      format.write(&temp, self).and_then(|_| Ok(std::fs::rename(&temp, &path)?))
+    {
+      let _ = std::fs::remove_file(&temp);
+      return Err(e);
+    }
+    for other in Workout::files_in(dir, &self.title) {
+      if other != path {
+        std::fs::remove_file(other)?;
+      }
     }
-    let path = format!("{}/{}.yml", DATA_DIR, self.title);
-    let f = File::create(path)?;
-    to_writer(f, &self)?;
     Ok(())
   }
 
+  /// The workout files in a directory named after the title, with any
+  /// extension a workout can be read from, such as `.yml` or `.yaml`.
+  pub fn files_in(dir: &Path, title: &str) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+      Ok(entries) => entries,
+      Err(_) => return vec![],
+    };
+    let mut paths: Vec<PathBuf> = entries
+      .filter_map(|entry| entry.ok().map(|e| e.path()))
+      .filter(|path| {
+        path.is_file()
+          && path.file_stem().is_some_and(|stem| stem == title)
+          && FileFormat::from_path(path).is_ok()
+      })
+      .collect();
+    paths.sort();
+    paths
+  }
+
+  /// Every exercise in the workout, in order, without repeats.
+  pub fn exercises(&self) -> Vec<&Exercise> {
+    let mut exercises: Vec<&Exercise> = vec![];
+    for exercise in self.sets.iter().flat_map(|set| set.iter()) {
+      if !exercises.iter().any(|e| e.name == exercise.name) {
+        exercises.push(exercise);
+      }
+    }
+    exercises
+  }
+
   pub fn duration(&self) -> Duration {
     let mut d = Duration::default();
 
     for screen in self.screens() {
-      d += *screen.screen_type.duration();
+      d += screen.screen_type.duration();
     }
 
     d
   }
 
+  /// The number of seconds the workout should take before the given screen.
+  pub fn time_before(&self, screen: usize) -> u64 {
+    self
+      .screens()
+      .iter()
+      .take(screen)
+      .map(|s| s.screen_type.duration().as_secs())
+      .sum()
+  }
+
+  /// The index of the screen to start from.
+  pub fn start_screen(&self, start: Start) -> usize {
+    match start {
+      Start::Screen(screen) => screen,
+      Start::Set(set) => self.set_start(set),
+    }
+  }
+
+  /// The index of the first screen of a (0 based) set, which is the warmup
+  /// or rest before it.
+  pub fn set_start(&self, set: usize) -> usize {
+    self
+      .sets
+      .iter()
+      .take(set)
+      .map(|set| {
+        // a warmup or rest, two more rests and three rounds of exercises.
+        let exercises: usize = set
+          .iter()
+          .map(|e| ScreenType::prescribed(0, &e.prescription).len())
+          .sum();
+        3 + 3 * exercises
+      })
+      .sum()
+  }
+
   pub fn screens(&self) -> Vec<Screen> {
     let mut result = vec![];
 
-    for i in 0..self.sets.len() {
-      if let Some(set) = self.sets.get(i) {
-        if i == 0 {
-          result.push(Screen::warmup_with_set(set, self.warmup_length * 60));
-        } else {
+    for (i, set) in self.sets.iter().enumerate() {
+      if i == 0 {
+        result.push(Screen::warmup_with_set(set, self.warmup_length * 60));
+      } else {
+        result.push(Screen::rest_with_set(set));
+      }
+      // three rounds of the set with a rest between each.
+      for round in 1..=3 {
+        if round > 1 {
           result.push(Screen::rest_with_set(set));
         }
-        result.push(Screen::exercise_set_with_rest(set, 1));
-        result.push(Screen::exercise_set_with_rest(set, 2));
-        result.push(Screen::exercise_set_with_rest(set, 3));
-        result.push(Screen::rest_with_set(set));
-        result.push(Screen::exercise_set_with_rest(set, 1));
-        result.push(Screen::exercise_set_with_rest(set, 2));
-        result.push(Screen::exercise_set_with_rest(set, 3));
-        result.push(Screen::rest_with_set(set));
-        result.push(Screen::exercise_set_with_rest(set, 1));
-        result.push(Screen::exercise_set_with_rest(set, 2));
-        if i == self.sets.len() - 1 {
-          result.push(Screen::exercise_set_with_cooldown(set, 3));
-        } else {
-          result.push(Screen::exercise_set_with_rest(set, 3));
+        for id in 1..=set.exercises.len() as u8 {
+          let last = i == self.sets.len() - 1
+            && round == 3
+            && id as usize == set.exercises.len();
+          if last {
+            result.extend(Screen::exercise_set_with_cooldown(set, id));
+          } else {
+            result.extend(Screen::exercise_set_with_rest(set, id));
+          }
         }
       }
     }
@@ -219,12 +348,22 @@ impl Workout {
     result
   }
 
-  pub fn run(&self) {
-    // Get the timers
-    let _elapsed: u64 = 0;
-    let _current: u64 = 0;
-    let _remaining = self.duration().as_secs();
+  /// Run the workout until the cooldown ends or q is pressed and return what
+  /// happened along the way.
+  pub fn run(&self) -> RunStats {
+    self.run_from(Start::Screen(0))
+  }
+
+  /// Run the workout starting part way through. The elapsed and remaining
+  /// times carry on from where the start is in the workout.
+  pub fn run_from(&self, start: Start) -> RunStats {
+    self.resume(Checkpoint::new(self, start, Local::now()))
+  }
 
+  /// Carry on running the workout from a checkpoint. The checkpoint is saved
+  /// every few seconds so the workout can be resumed again if it is
+  /// interrupted. It is cleared once the workout is finished.
+  pub fn resume(&self, mut checkpoint: Checkpoint) -> RunStats {
     // Get the screens and times
     let screens = self.screens();
     let cooldown = Screen::cooldown();
@@ -250,39 +389,77 @@ impl Workout {
     audio.add("bell", sound_path.join(Workout::BELL).to_str().unwrap());
     audio.add("whistle", sound_path.join(Workout::WHISTLE).to_str().unwrap());
 
+    // read before raw mode, so a problem with the settings is logged first.
+    let hooks = Hooks::load();
+
     // Go into raw mode
     let mut stdout = stdout().into_raw_mode().unwrap();
 
-    // start thread and create channel
-    let (tx, rx) = mpsc::channel();
-
-    let _handle = spawn(move || {
-      // wait for input then clear the screen in preparation for the next set.
-      // Get stdin and lock it.
-      let stdin = stdin();
-      let keys = stdin.keys();
-      for key in keys.flatten() {
-        if tx.send(key).is_err() {
-          return;
-        }
+    // start reading keys in the background
+    let rx = KeyReader::spawn();
+    // and take controls from the remote if there is one.
+    let remote = Remote::get();
+    if let Some(remote) = remote {
+      remote.drain();
+    }
+    // tell whatever is listening for events what happens, and run the hooks
+    // from the settings.
+    let events = Events::get();
+    let send = |event: Event| {
+      hooks.fire(&self.title, &event);
+      if let Some(events) = events {
+        events.send(event);
       }
-    });
+    };
 
     // Iterate through the screens.
-    let mut i = 0;
-    let mut current_time = 0;
+    let mut i = checkpoint.screen.min(screens.len() - 1);
+    let mut current_time = checkpoint.current_time;
+    let mut stats = checkpoint.stats.clone();
     let mut overtime = false;
+    let mut paused = false;
+    let mut saved_at = stats.elapsed_secs;
+    let mut new_screen = true;
+    send(Event::SessionStart {
+      title: self.title.clone(),
+      screens: screens.len(),
+      total_secs: total_time,
+      screen: i,
+      elapsed_secs: stats.elapsed_secs,
+    });
     loop {
+      // save where we are every few seconds.
+      if stats.elapsed_secs >= saved_at + Checkpoint::INTERVAL_SECS {
+        saved_at = stats.elapsed_secs;
+        checkpoint.update(i, current_time, &stats);
+        // there is nowhere to report an error while the workout is running.
+        let _ = checkpoint.save();
+      }
+
       // get the current screen
-      let screen = screens.get(i).or(Some(&cooldown)).unwrap();
+      let screen = screens.get(i).unwrap_or(&cooldown);
       let time_elapsed = *times.get(i).unwrap();
       let current_total = screen.screen_type.duration().as_secs();
-      let current_time_remaining = if current_time > current_total {
+      // screens that wait for a key press have no time limit.
+      let untimed = screen.screen_type.waits_for_key();
+      let current_time_remaining = if untimed {
+        0
+      } else if current_time > current_total {
         overtime = true;
         0
       } else {
         current_total - current_time
       };
+      if new_screen {
+        new_screen = false;
+        send(Event::ScreenStart {
+          screen: i,
+          screen_type: screen.screen_type.name(),
+          heading: screen.screen_type.heading(),
+          name: screen.name.clone(),
+          duration_secs: if untimed { None } else { Some(current_total) },
+        });
+      }
       let total_time_elapsed = time_elapsed + current_time;
       let total_time_remaining = if total_time_elapsed > total_time {
         overtime = true;
@@ -292,22 +469,52 @@ impl Workout {
       };
 
       // check if a sound needs to be played.
-      if current_time_remaining == 7 && i < screens.len() - 1 {
+      if !untimed
+        && !paused
+        && current_time_remaining == Workout::COUNTDOWN_SECS
+        && i < screens.len() - 1
+      {
         audio.play("tick");
       }
-      if current_time == 0 {
+      if current_time == 0 && !paused {
         match screen.screen_type {
           ScreenType::Cooldown(_) | ScreenType::Rest(_) => {
             audio.play("whistle");
           }
-          ScreenType::Exercise(_, _) => {
+          ScreenType::WarmUp(_) => {}
+          _ => {
             audio.play("bell");
           }
-          _ => {}
         }
       }
 
-      let current_time_remaining = current_time_remaining.as_time();
+      // let the remote know what's on the screen.
+      if let Some(remote) = remote {
+        remote.set(Some(Live {
+          title: self.title.clone(),
+          screen: i,
+          screens: screens.len(),
+          heading: screen.screen_type.heading(),
+          kind: screen.screen_type.kind(),
+          name: screen.name.clone(),
+          remaining_secs: if untimed {
+            None
+          } else {
+            Some(current_time_remaining)
+          },
+          elapsed_secs: current_time,
+          total_remaining_secs: total_time_remaining,
+          total_secs: total_time,
+          next: screens.get(i + 1).map(|s| s.name.clone()),
+          paused,
+        }));
+      }
+
+      let current_time_remaining = if untimed {
+        "Press Space when done".to_string()
+      } else {
+        current_time_remaining.as_time()
+      };
       let total_time_remaining = total_time_remaining.as_time();
 
       // show the screen
@@ -317,7 +524,7 @@ impl Workout {
         {}Total Remaining: {}\n\
         {}Current Elapsed: {}\n\
         {}Current Remaining: {}\n\
-        {}{}{}\n{}",
+        {}{}{}{}\n{}",
         clear_screen(),
         just_left(),
         total_time_elapsed.as_time(),
@@ -328,73 +535,126 @@ impl Workout {
         just_left(),
         if overtime { "OVERTIME!" } else { current_time_remaining.as_str() },
         style::Bold,
-        match screen.screen_type {
-          ScreenType::WarmUp(_) => "WARMING UP!".to_string(),
-          ScreenType::Rest(_) => "REST!".to_string(),
-          ScreenType::Exercise(i, _) => format!("SET {}", i),
-          ScreenType::Cooldown(_) => "Aah - Feel better?".to_string(),
-        },
+        screen.screen_type.heading(),
+        if paused { " - PAUSED (press p to carry on)" } else { "" },
         style::Reset,
         screen,
       )
       .unwrap();
       stdout.flush().unwrap();
 
-      if let Ok(key) = rx.try_recv() {
-        match key {
-          // q - quits the program
-          Key::Char('q') => {
-            return;
+      // keys are checked first, then anything sent from the remote.
+      let control = match rx.try_recv() {
+        Ok(key) => Control::from_key(key),
+        Err(_) => remote.and_then(|r| r.try_recv()),
+      };
+      if let Some(control) = control {
+        let from = i;
+        let outcome = match control {
+          // the checkpoint is kept so the workout can be resumed later.
+          Control::Quit => {
+            checkpoint.update(i, current_time, &stats);
+            let _ = checkpoint.save();
+            stats
+              .intervals
+              .push(screen.interval(current_time, Outcome::Stopped));
+            if let Some(remote) = remote {
+              remote.set(None);
+            }
+            send(Event::Quit { screen: i, elapsed_secs: stats.elapsed_secs });
+            return stats;
           }
-          // up and left will both go back one screen.
-          Key::Up | Key::Left => {
-            decrement(&mut i);
-            current_time = 0;
-            write!(stdout, "{}", clear_screen()).unwrap();
-            stdout.flush().unwrap();
+          Control::Pause => {
+            paused = !paused;
+            send(if paused {
+              Event::Pause { screen: i }
+            } else {
+              Event::Resume { screen: i }
+            });
             continue;
           }
-          Key::Home => {
+          Control::Previous => {
+            decrement(&mut i);
+            Some(Outcome::Skipped)
+          }
+          Control::First => {
             i = 0;
-            current_time = 0;
-            write!(stdout, "{}", clear_screen()).unwrap();
-            stdout.flush().unwrap();
-            continue;
+            Some(Outcome::Skipped)
           }
-          // down and right will both go forward one screen.
-          Key::Down | Key::Right => {
-            if i < screens.len() - 1 {
-              increment(&mut i, screens.len() - 1);
-              current_time = 0;
-              write!(stdout, "{}", clear_screen()).unwrap();
-              stdout.flush().unwrap();
-              continue;
-            }
+          Control::Next if i < screens.len() - 1 => {
+            increment(&mut i, screens.len() - 1);
+            Some(Outcome::Skipped)
+          }
+          // finishing a set of reps.
+          Control::Done if untimed => {
+            increment(&mut i, screens.len() - 1);
+            Some(Outcome::Completed)
           }
-          Key::End => {
+          Control::Last => {
             i = screens.len() - 1;
-            current_time = 0;
-            write!(stdout, "{}", clear_screen()).unwrap();
-            stdout.flush().unwrap();
-            continue;
+            Some(Outcome::Skipped)
+          }
+          _ => None,
+        };
+        if let Some(outcome) = outcome {
+          if outcome == Outcome::Skipped {
+            send(Event::Skip { from, to: i });
           }
-          _ => (),
+          stats.intervals.push(screen.interval(current_time, outcome));
+          current_time = 0;
+          new_screen = true;
+          write!(stdout, "{}", clear_screen()).unwrap();
+          stdout.flush().unwrap();
+          continue;
         }
       }
 
       // update timer.
       sleep(Duration::from_secs(1));
+      if paused {
+        stats.paused_secs += 1;
+        continue;
+      }
       current_time += 1;
+      stats.elapsed_secs += 1;
+      let remaining_secs = current_total.saturating_sub(current_time);
+      send(Event::Tick {
+        screen: i,
+        elapsed_secs: current_time,
+        remaining_secs: if untimed { None } else { Some(remaining_secs) },
+        total_remaining_secs: total_time
+          .saturating_sub(time_elapsed + current_time),
+      });
+      if !untimed
+        && remaining_secs == Workout::COUNTDOWN_SECS
+        && i < screens.len() - 1
+      {
+        send(Event::Countdown { screen: i, remaining_secs });
+      }
 
       // check if current timer is >= screen's duration and increment the screen if necessary.
-      if current_time >= current_total && i < screens.len() - 1 {
+      if !untimed && current_time >= current_total {
+        stats.intervals.push(screen.interval(current_time, Outcome::Completed));
+        // the workout is over once the cooldown ends.
+        if i == screens.len() - 1 {
+          let _ = Checkpoint::clear();
+          if let Some(remote) = remote {
+            remote.set(None);
+          }
+          send(Event::Finish { elapsed_secs: stats.elapsed_secs });
+          return stats;
+        }
         i += 1;
         current_time = 0;
+        new_screen = true;
         continue;
       }
     }
   }
 
+  /// How many seconds before the end of a screen the tick sound plays.
+  const COUNTDOWN_SECS: u64 = 7;
+
   const TICK: &'static str = "tick.wav";
   const BELL: &'static str = "bell.wav";
   const WHISTLE: &'static str = "whistle.wav";
@@ -409,36 +669,36 @@ impl Default for Workout {
       ExerciseType::UpperBodyAbs,
       vec![
         ExerciseSet {
-          exercises: (
+          exercises: vec![
             Exercise::new("Do stuff", "This is how you do that stuff"),
             Exercise::new(
               "Do other stuff",
               "This is how you do that other stuff",
             ),
             Exercise::new("Do more stuff", "This is how you do that stuff"),
-          ),
+          ],
           exercise_type: ExerciseType::LowerBodyAbs,
         },
         ExerciseSet {
-          exercises: (
+          exercises: vec![
             Exercise::new("Do stuff", "This is how you do that stuff"),
             Exercise::new(
               "Do other stuff",
               "This is how you do that other stuff",
             ),
             Exercise::new("Do more stuff", "This is how you do that stuff"),
-          ),
+          ],
           exercise_type: ExerciseType::LowerBodyAbs,
         },
         ExerciseSet {
-          exercises: (
+          exercises: vec![
             Exercise::new("Do stuff", "This is how you do that stuff"),
             Exercise::new(
               "Do other stuff",
               "This is how you do that other stuff",
             ),
             Exercise::new("Do more stuff", "This is how you do that stuff"),
-          ),
+          ],
           exercise_type: ExerciseType::LowerBodyAbs,
         },
       ],
@@ -457,3 +717,25 @@ fn decrement(i: &mut usize) {
     *i -= 1;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_finds_the_first_screen_of_each_set() {
+    let mut workout = Workout::default();
+    workout.sets[0].exercises[1].prescription =
+      prescription::Prescription::PerSide { secs: 30 };
+    let screens = workout.screens();
+
+    assert_eq!(workout.set_start(0), 0);
+    assert!(matches!(screens[0].screen_type, ScreenType::WarmUp(_)));
+    for set in 1..workout.sets.len() {
+      let start = workout.set_start(set);
+      assert!(matches!(screens[start].screen_type, ScreenType::Rest(_)));
+      assert!(screens[start + 1].screen_type.is_exercise());
+      assert!(screens[start - 1].screen_type.is_exercise());
+    }
+  }
+}
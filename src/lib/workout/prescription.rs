@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// How an exercise is prescribed. Most exercises are simply timed intervals,
+/// but some are done for a number of reps, held for a length of time or done
+/// once for each side of the body.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum Prescription {
+  /// Work for the default interval length.
+  #[default]
+  Timed,
+  /// Do a number of reps and press a key when done. If a cap is given the
+  /// screen advances on its own once the cap (in seconds) runs out.
+  Reps { count: u32, cap: Option<u64> },
+  /// Hold a position for the given number of seconds.
+  Hold { secs: u64 },
+  /// Work each side for the given number of seconds. This is split into two
+  /// screens, one for each side.
+  PerSide { secs: u64 },
+}
+
+impl Prescription {
+  pub fn is_timed(&self) -> bool {
+    *self == Prescription::Timed
+  }
+
+  /// Whether any of its numbers is 0, which would give a screen with nothing
+  /// to do.
+  pub fn has_zero(&self) -> bool {
+    match *self {
+      Prescription::Timed => false,
+      Prescription::Reps { count, cap } => count == 0 || cap == Some(0),
+      Prescription::Hold { secs } | Prescription::PerSide { secs } => secs == 0,
+    }
+  }
+}
+
+impl fmt::Display for Prescription {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Prescription::Timed => Ok(()),
+      Prescription::Reps { count, cap: None } => write!(f, "{} reps", count),
+      Prescription::Reps { count, cap: Some(cap) } => {
+        write!(f, "{} reps (max {}s)", count, cap)
+      }
+      Prescription::Hold { secs } => write!(f, "hold {}s", secs),
+      Prescription::PerSide { secs } => write!(f, "{}s each side", secs),
+    }
+  }
+}
+
+/// Which side of a [Prescription::PerSide] exercise is being worked.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+  First,
+  Second,
+}
+
+impl fmt::Display for Side {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Side::First => "SIDE 1",
+        Side::Second => "SIDE 2",
+      }
+    )
+  }
+}
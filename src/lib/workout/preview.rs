@@ -0,0 +1,56 @@
+use super::Workout;
+use crate::lib::{screen::ScreenType, timer::Timer};
+use chrono::{DateTime, Local};
+
+/// How the sets of every workout are run.
+pub const MODE: &str = "3 rounds of each set with a rest between rounds";
+
+/// The length of each kind of interval in the workout.
+pub fn timings(workout: &Workout) -> String {
+  format!(
+    "{} min warmup, {}s per exercise, {}s rest, {} min cooldown",
+    workout.warmup_length,
+    ScreenType::exercise(0).duration().as_secs(),
+    ScreenType::rest().duration().as_secs(),
+    ScreenType::cooldown().duration().as_secs() / 60,
+  )
+}
+
+/// The lines of the page shown before a workout starts. It lists every set
+/// and exercise along with how long the workout takes and when it was last
+/// done.
+pub fn preview(
+  workout: &Workout,
+  last_done: Option<DateTime<Local>>,
+) -> Vec<String> {
+  let mut lines = vec![
+    workout.title.clone(),
+    String::new(),
+    format!("Day:       {}", workout.day),
+    format!("Type:      {}", workout.workout_type),
+    format!("Duration:  {}", workout.duration().as_secs().as_time()),
+    format!("Timings:   {}", timings(workout)),
+    format!("Mode:      {}", MODE),
+    format!(
+      "Last done: {}",
+      match last_done {
+        Some(date) => date.format("%A %Y-%m-%d").to_string(),
+        None => "never".to_string(),
+      }
+    ),
+  ];
+  if !workout.link.is_empty() {
+    lines.push(format!("Link:      {}", workout.link));
+  }
+
+  for (n, set) in workout.sets.iter().enumerate() {
+    lines.push(String::new());
+    lines.push(format!("Set {}", n + 1));
+    for exercise in set.iter() {
+      lines.push(format!("  {}{}", exercise.name, exercise.details()));
+      lines.push(format!("    {}", exercise.description));
+    }
+  }
+
+  lines
+}
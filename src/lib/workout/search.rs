@@ -0,0 +1,160 @@
+use super::Workout;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// The part of a workout a search matched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+  Title,
+  Exercise(String),
+  Description(String),
+}
+
+impl Field {
+  /// Matches on the title rank above exercise names, which rank above
+  /// descriptions.
+  fn bonus(&self) -> i64 {
+    match self {
+      Field::Title => 30,
+      Field::Exercise(_) => 20,
+      Field::Description(_) => 0,
+    }
+  }
+}
+
+impl fmt::Display for Field {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Field::Title => write!(f, "title"),
+      Field::Exercise(name) => write!(f, "exercise: {}", name),
+      Field::Description(name) => write!(f, "description of {}", name),
+    }
+  }
+}
+
+/// A workout that matched a search along with how well it matched.
+#[derive(Debug, Clone)]
+pub struct SearchResult<'a> {
+  pub workout: &'a Workout,
+  pub field: Field,
+  pub score: i64,
+}
+
+/// Score how well `query` matches `text`, ignoring case. Every character of
+/// the query has to appear in the text in order. Whole substrings score best,
+/// then characters that follow each other or start a word. Returns `None`
+/// when the query doesn't match at all.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+  let query = query.trim().to_lowercase();
+  let text = text.to_lowercase();
+  if query.is_empty() {
+    return None;
+  }
+
+  if let Some(position) = text.find(&query) {
+    let word_start = position == 0
+      || !text[..position].chars().last().unwrap().is_alphanumeric();
+    return Some(
+      100 + query.len() as i64 * 10 + if word_start { 20 } else { 0 }
+        - position.min(20) as i64,
+    );
+  }
+
+  let mut score = 0;
+  let mut query_chars = query.chars().peekable();
+  let mut last_matched = false;
+  let mut previous = ' ';
+  for c in text.chars() {
+    match query_chars.peek() {
+      Some(q) if *q == c => {
+        score += 10;
+        if last_matched {
+          score += 5;
+        }
+        if !previous.is_alphanumeric() {
+          score += 5;
+        }
+        query_chars.next();
+        last_matched = true;
+      }
+      Some(_) => {
+        score -= 1;
+        last_matched = false;
+      }
+      None => break,
+    }
+    previous = c;
+  }
+
+  if query_chars.peek().is_none() {
+    Some(score.max(1))
+  } else {
+    None
+  }
+}
+
+/// Search the titles, exercise names and descriptions of the workouts. Each
+/// workout shows up at most once with its best match, best matches first.
+pub fn search<'a>(
+  workouts: &'a [Workout],
+  query: &str,
+) -> Vec<SearchResult<'a>> {
+  let mut results: Vec<SearchResult> = workouts
+    .iter()
+    .filter_map(|workout| {
+      let mut fields = vec![(Field::Title, workout.title.as_str())];
+      for exercise in workout.exercises() {
+        fields.push((Field::Exercise(exercise.name.clone()), &exercise.name));
+        fields.push((
+          Field::Description(exercise.name.clone()),
+          &exercise.description,
+        ));
+      }
+
+      fields
+        .into_iter()
+        .filter_map(|(field, text)| {
+          fuzzy_score(query, text).map(|score| SearchResult {
+            workout,
+            score: score + field.bonus(),
+            field,
+          })
+        })
+        .max_by_key(|result| result.score)
+    })
+    .collect();
+
+  results.sort_by(|a, b| {
+    b.score.cmp(&a.score).then_with(|| a.workout.title.cmp(&b.workout.title))
+  });
+  results
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn it_prefers_substrings_over_scattered_matches() {
+    let exact = fuzzy_score("push", "Diamond Push-ups").unwrap();
+    let scattered = fuzzy_score("push", "Plank Up Shoulder Hold").unwrap();
+    assert!(exact > scattered);
+    assert_eq!(fuzzy_score("burpee", "Diamond Push-ups"), None);
+  }
+
+  #[test]
+  fn it_finds_workouts_by_exercise_name() {
+    let mut with = Workout { title: "Upper".to_string(), ..Workout::default() };
+    with.sets[1].exercises[1].name = "Diamond Push-ups".to_string();
+    let without = Workout { title: "Lower".to_string(), ..Workout::default() };
+
+    let workouts = vec![without, with];
+    let results = search(&workouts, "diamond push");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].workout.title, "Upper");
+    assert_eq!(
+      results[0].field,
+      Field::Exercise("Diamond Push-ups".to_string())
+    );
+  }
+}
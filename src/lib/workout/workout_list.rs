@@ -1,5 +1,9 @@
 use super::{
-  super::enums::{DayOfWeek, ExerciseType},
+  super::{
+    enums::{DayOfWeek, ExerciseType},
+    history::Session,
+  },
+  filter::Filter,
   Workout,
 };
 
@@ -9,6 +13,10 @@ pub trait WorkoutList {
 
   /// Filter by the workout type
   fn filter_by_type(&self, _type: &ExerciseType) -> Vec<&Workout>;
+
+  /// The workouts matching a filter. The sessions are used to find when each
+  /// workout was last done.
+  fn query(&self, _filter: &Filter, _sessions: &[Session]) -> Vec<&Workout>;
 }
 
 impl WorkoutList for Vec<Workout> {
@@ -19,6 +27,10 @@ impl WorkoutList for Vec<Workout> {
   fn filter_by_type(&self, workout_type: &ExerciseType) -> Vec<&Workout> {
     self.iter().filter(|w| w.workout_type == *workout_type).collect()
   }
+
+  fn query(&self, filter: &Filter, sessions: &[Session]) -> Vec<&Workout> {
+    self.iter().filter(|w| filter.matches(w, sessions)).collect()
+  }
 }
 impl WorkoutList for &[Workout] {
   fn filter_by_day(&self, day: &DayOfWeek) -> Vec<&Workout> {
@@ -28,4 +40,8 @@ impl WorkoutList for &[Workout] {
   fn filter_by_type(&self, workout_type: &ExerciseType) -> Vec<&Workout> {
     self.iter().filter(|w| w.workout_type == *workout_type).collect()
   }
+
+  fn query(&self, filter: &Filter, sessions: &[Session]) -> Vec<&Workout> {
+    self.iter().filter(|w| filter.matches(w, sessions)).collect()
+  }
 }
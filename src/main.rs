@@ -1,33 +1,102 @@
+#![allow(special_module_name)]
+
 use anyhow::Result;
 use lib::{
-  enums::*,
+  cli::{Command, USAGE},
+  events::{self, Events},
+  file_format::SCHEMA,
+  settings::Settings,
   util::*,
-  workout::{workout_list::WorkoutList, Workout, WorkoutImport},
+  workout::importer::Importer,
 };
-use rand::prelude::SliceRandom;
-use std::{process::exit, str::FromStr};
-use terminal_menu::{button, label, menu, mut_menu, run, TerminalMenuItem};
-use workout_paths::import_path;
+use workout_paths::{failed_path, import_path};
 
+mod commands;
+mod editor;
 pub mod lib;
+mod menus;
+mod sessions;
 
 fn main() -> Result<()> {
-  import_workouts()?;
-  show_workouts()?;
+  match Command::parse(std::env::args().skip(1))? {
+    Command::Menu => {
+      serve()?;
+      import_workouts()?;
+      sessions::offer_resume()?;
+      menus::show_workouts()?;
+    }
+    Command::Search(query) => commands::search(&query)?,
+    Command::List(filter) => commands::list(&filter)?,
+    Command::CalendarExport { time, output } => {
+      commands::calendar_export(time, output)?
+    }
+    Command::HistoryExport { format, since, intervals, output } => {
+      commands::history_export(format, since, intervals, output)?
+    }
+    Command::HistoryTcx { since, output } => {
+      commands::history_tcx(since, output)?
+    }
+    Command::HistoryImport(path) => commands::history_import(&path)?,
+    Command::Export { title, format, output } => {
+      commands::export(title.as_deref(), format, output)?
+    }
+    Command::Run { title, from_set, events } => {
+      serve()?;
+      if let Some(target) = events {
+        Events::install(&target)?;
+      }
+      commands::run(&title, from_set)?;
+      // make sure whatever is listening hears how it ended.
+      if let Some(events) = Events::get() {
+        events.flush();
+      }
+    }
+    Command::Import { convert, on_conflict, paths } => {
+      commands::import(convert, on_conflict, paths)?
+    }
+    Command::Edit { title, external } => commands::edit(&title, external)?,
+    Command::Undo => commands::undo()?,
+    Command::Duplicate { title, new_title, edit } => {
+      commands::duplicate(&title, &new_title, edit)?
+    }
+    Command::Rename { title, new_title } => {
+      commands::rename(&title, &new_title)?
+    }
+    Command::Delete(title) => commands::delete(&title)?,
+    Command::Trash => commands::trash()?,
+    Command::Restore(title) => commands::restore(&title)?,
+    Command::Upgrade => commands::upgrade()?,
+    Command::Schema { events: false } => print!("{}", SCHEMA),
+    Command::Schema { events: true } => print!("{}", events::SCHEMA),
+    Command::Help => println!("{}", USAGE),
+  }
   Ok(())
 }
 
-const FILTER_BY_DAY: &str = "Filter by day of the week";
-const FILTER_BY_TYPE: &str = "Filter by workout type";
-const BACK: &str = "..";
-const RANDOM: &str = "Random Workout!";
-const QUIT: &str = "Quit";
+/// Start the HTTP server if the settings ask for one.
+fn serve() -> Result<()> {
+  if let Some(address) = Settings::load()?.http_address {
+    #[cfg(feature = "http")]
+    {
+      lib::http::start(&address)?;
+      println!("Serving the workouts on http://{}", address);
+    }
+    #[cfg(not(feature = "http"))]
+    println!(
+      "http_address is set to {} but this build doesn't have the http \
+       feature - see the README",
+      address
+    );
+  }
+  Ok(())
+}
 
 fn import_workouts() -> Result<()> {
   println!("Checking for imports...");
-  let workouts = WorkoutImport::load_all()?;
+  let importer = Importer::new()?;
+  let report = importer.run(&importer.pending()?, commands::ask_conflict)?;
 
-  if workouts.is_empty() {
+  if report.is_empty() {
     println!("None found.");
     let path = import_path().join("example.yml");
     println!(
@@ -35,117 +104,13 @@ fn import_workouts() -> Result<()> {
       path
     );
   } else {
-    println!("Saving imports...");
-    for workout in workouts.into_iter() {
-      workout.upgrade().save()?;
+    print!("{}", report);
+    if !report.failed.is_empty() {
+      println!("Files that failed were moved to {:?}", failed_path());
     }
-    println!("Imports saved!");
   }
 
   pause()?;
 
   Ok(())
 }
-
-fn show_workouts() -> Result<()> {
-  // first load the workouts
-  let workouts = Workout::load_all()?;
-
-  let mut result = main_menu();
-  let mut filter;
-  filter = filter_menu(&result, &workouts);
-  loop {
-    let title = if let Ok(filter) = DayOfWeek::from_str(filter.as_str()) {
-      workout_menu(Filter::DayOfWeek(filter), &workouts)
-    } else if let Ok(filter) = ExerciseType::from_str(filter.as_str()) {
-      workout_menu(Filter::WorkoutType(filter), &workouts)
-    } else if filter == BACK {
-      result = main_menu();
-      filter = filter_menu(&result, &workouts);
-      continue;
-    } else {
-      exit(0);
-    };
-
-    if let Some(workout) = workouts.iter().find(|w| w.title == title) {
-      workout.run();
-    } else if title == BACK {
-      filter = filter_menu(&result, &workouts);
-    } else if title == RANDOM {
-      if let Some(workout) =
-        if let Ok(filter) = DayOfWeek::from_str(filter.as_str()) {
-          let filtered = workouts.filter_by_day(&filter);
-          filtered.choose(&mut rand::thread_rng()).cloned()
-        } else if let Ok(filter) = ExerciseType::from_str(filter.as_str()) {
-          let filtered = workouts.filter_by_type(&filter);
-          filtered.choose(&mut rand::thread_rng()).cloned()
-        } else {
-          panic!("Invalid filter type");
-        }
-      {
-        workout.run();
-      }
-    } else {
-      break;
-    }
-  }
-
-  Ok(())
-}
-
-fn main_menu() -> String {
-  let list: Vec<TerminalMenuItem> =
-    vec![button(FILTER_BY_DAY), button(FILTER_BY_TYPE), button(QUIT)];
-  show_menu(list)
-}
-
-fn filter_menu(result: &str, workouts: &[Workout]) -> String {
-  let mut list: Vec<TerminalMenuItem> = match result {
-    FILTER_BY_TYPE => ExerciseType::VALUES
-      .iter()
-      .filter(|v| !workouts.filter_by_type(v).is_empty())
-      .map(|v| button(v.to_string()))
-      .collect(),
-    FILTER_BY_DAY => DayOfWeek::VALUES
-      .iter()
-      .filter(|v| !workouts.filter_by_day(v).is_empty())
-      .map(|v| button(v.to_string()))
-      .collect(),
-    BACK => return main_menu(),
-    _ => exit(0),
-  };
-
-  list.insert(0, label("Please select a filter:"));
-  list.insert(1, button(BACK));
-  list.push(button(QUIT));
-
-  show_menu(list)
-}
-
-fn workout_menu(filter: Filter, workouts: &[Workout]) -> String {
-  let mut list: Vec<TerminalMenuItem> = match filter {
-    Filter::DayOfWeek(filter) => {
-      workouts.filter_by_day(&filter).iter().map(|w| button(&w.title)).collect()
-    }
-    Filter::WorkoutType(filter) => workouts
-      .filter_by_type(&filter)
-      .iter()
-      .map(|w| button(&w.title))
-      .collect(),
-  };
-
-  list.insert(0, label("Choose a workout"));
-  list.insert(1, button(BACK));
-  list.push(button(RANDOM));
-  list.push(button(QUIT));
-  show_menu(list)
-}
-
-fn show_menu(list: Vec<TerminalMenuItem>) -> String {
-  let m = menu(list);
-  print!("{}", clear_screen());
-  run(&m);
-
-  let result = mut_menu(&m).selected_item_name().to_owned();
-  result
-}
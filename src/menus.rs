@@ -0,0 +1,511 @@
+use crate::{
+  editor,
+  lib::{
+    enums::*,
+    history::{self, Session},
+    menu::{breadcrumbs::Breadcrumbs, Menu, Selection},
+    settings::Settings,
+    util::*,
+    workout::{
+      edit::Edits,
+      filter::Filter,
+      generator::{self, GeneratorOptions},
+      preview, search,
+      workout_list::WorkoutList,
+      Start, Workout,
+    },
+  },
+  sessions::do_workout,
+};
+use anyhow::Result;
+use rand::prelude::SliceRandom;
+use std::fmt;
+use std::fmt::Formatter;
+use workout_paths::data_path;
+
+/// A page of the interactive menus.
+#[derive(Debug, Clone, PartialEq)]
+enum Page {
+  Main,
+  Days,
+  Types,
+  Workouts(Filter),
+  /// The workout with this title.
+  Preview(String),
+  StartFromSet(String),
+  Search(String),
+  History,
+  Trash,
+}
+
+impl fmt::Display for Page {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Page::Main => write!(f, "Workouts"),
+      Page::Days => write!(f, "By day"),
+      Page::Types => write!(f, "By type"),
+      Page::Workouts(filter) => write!(f, "{}", filter),
+      Page::Preview(title) => write!(f, "{}", title),
+      Page::StartFromSet(_) => write!(f, "Start from set"),
+      Page::Search(query) => write!(f, "Search {:?}", query),
+      Page::History => write!(f, "Exercise history"),
+      Page::Trash => write!(f, "Trash"),
+    }
+  }
+}
+
+/// Where to go after a menu item has been chosen.
+enum Next {
+  Open(Page),
+  Stay,
+  Back,
+  /// Load the workouts again after they've been changed.
+  Reload,
+}
+
+/// Show the menus until the user quits or goes back from the first page.
+pub fn show_workouts() -> Result<()> {
+  let mut workouts = Workout::load_all()?;
+  let mut crumbs = Breadcrumbs::new(Page::Main);
+
+  while let Some(page) = crumbs.current().cloned() {
+    let heading = crumbs.trail();
+    let selection = match &page {
+      Page::Main => main_menu(heading, &workouts).run(),
+      Page::Days => days_menu(heading, &workouts).run(),
+      Page::Types => types_menu(heading, &workouts).run(),
+      Page::Workouts(filter) => workout_menu(heading, filter, &workouts)?.run(),
+      Page::Preview(title) | Page::StartFromSet(title) => {
+        match workouts.iter().find(|w| &w.title == title) {
+          Some(workout) if matches!(page, Page::Preview(_)) => {
+            preview_menu(heading, workout)?.run()
+          }
+          Some(workout) => set_menu(heading, workout).run(),
+          None => Selection::Back,
+        }
+      }
+      Page::Search(query) => search_menu(heading, &workouts, query).run(),
+      Page::History => history_menu(heading)?.run(),
+      Page::Trash => trash_menu(heading)?.run(),
+    };
+
+    match selection {
+      Selection::Chose(next) => match next? {
+        Next::Open(page) => crumbs.push(page),
+        Next::Stay => {}
+        Next::Back => crumbs.back(),
+        Next::Reload => workouts = Workout::load_dir(&data_path())?.0,
+      },
+      Selection::Back => crumbs.back(),
+      Selection::Quit => crumbs.clear(),
+    }
+  }
+
+  Ok(())
+}
+
+fn main_menu(heading: String, workouts: &[Workout]) -> Menu<'_, Result<Next>> {
+  Menu::new(heading)
+    .without_back()
+    .item("Filter by day of the week", || Ok(Next::Open(Page::Days)))
+    .item("Filter by workout type", || Ok(Next::Open(Page::Types)))
+    .item("Custom filter", || Ok(Next::Open(Page::Workouts(custom_filter()?))))
+    .item("Search", || {
+      print!("{}", clear_screen());
+      Ok(Next::Open(Page::Search(prompt("Search for: ")?)))
+    })
+    .item("Generate a workout", move || {
+      generate_workout(workouts)?;
+      Ok(Next::Stay)
+    })
+    .item("Exercise history", || Ok(Next::Open(Page::History)))
+    .item("New workout", || {
+      print!("{}", clear_screen());
+      let title = prompt("Title: ")?;
+      if title.is_empty() {
+        return Ok(Next::Stay);
+      }
+      let workout = Workout::new(
+        &title,
+        None,
+        DayOfWeek::today(),
+        ExerciseType::LowerBodyAbs,
+        vec![],
+      );
+      editor::edit(workout, None)?;
+      Ok(Next::Reload)
+    })
+    .item("Trash", || Ok(Next::Open(Page::Trash)))
+    .item("Undo the last edit", || {
+      print!("{}", clear_screen());
+      match Edits::new()?.undo() {
+        Ok(Some(done)) => println!("{}", done),
+        Ok(None) => println!("There's nothing to undo."),
+        Err(e) => println!("Couldn't undo it: {}", e),
+      }
+      pause()?;
+      Ok(Next::Reload)
+    })
+}
+
+fn days_menu(heading: String, workouts: &[Workout]) -> Menu<'_, Result<Next>> {
+  let mut menu = Menu::new(heading).line("Please select a filter:");
+  for day in DayOfWeek::VALUES.iter() {
+    if !workouts.filter_by_day(day).is_empty() {
+      let day = *day;
+      menu = menu.item(day.to_string(), move || {
+        Ok(Next::Open(Page::Workouts(Filter::Day(day))))
+      });
+    }
+  }
+  menu
+}
+
+fn types_menu(heading: String, workouts: &[Workout]) -> Menu<'_, Result<Next>> {
+  let mut menu = Menu::new(heading).line("Please select a filter:");
+  for workout_type in ExerciseType::VALUES.iter() {
+    if !workouts.filter_by_type(workout_type).is_empty() {
+      let workout_type = *workout_type;
+      menu = menu.item(workout_type.to_string(), move || {
+        Ok(Next::Open(Page::Workouts(Filter::Type(workout_type))))
+      });
+    }
+  }
+  menu
+}
+
+fn workout_menu(
+  heading: String,
+  filter: &Filter,
+  workouts: &[Workout],
+) -> Result<Menu<'static, Result<Next>>> {
+  let sessions = Session::load_all()?;
+  let titles: Vec<String> =
+    workouts.query(filter, &sessions).iter().map(|w| w.title.clone()).collect();
+
+  let mut menu = Menu::new(heading).line(if titles.is_empty() {
+    "No workouts match"
+  } else {
+    "Choose a workout"
+  });
+  for title in titles.clone() {
+    menu = menu.item(title.clone(), move || {
+      Ok(Next::Open(Page::Preview(title.clone())))
+    });
+  }
+  Ok(menu.item("Random Workout!", move || {
+    Ok(match titles.choose(&mut rand::thread_rng()) {
+      Some(title) => Next::Open(Page::Preview(title.clone())),
+      None => Next::Stay,
+    })
+  }))
+}
+
+/// Ask for each part of a filter, leaving out the parts that are skipped.
+fn custom_filter() -> Result<Filter> {
+  let mut filters = vec![];
+
+  let mut menu = Menu::new("Which day?").without_back().without_quit();
+  menu = menu.item("Any", || None);
+  for day in DayOfWeek::VALUES.iter() {
+    menu = menu.item(day.to_string(), move || Some(Filter::Day(*day)));
+  }
+  if let Selection::Chose(Some(filter)) = menu.run() {
+    filters.push(filter);
+  }
+
+  let mut menu =
+    Menu::new("What type of workout?").without_back().without_quit();
+  menu = menu.item("Any", || None);
+  for workout_type in ExerciseType::VALUES.iter() {
+    menu = menu.item(workout_type.to_string(), move || {
+      Some(Filter::Type(*workout_type))
+    });
+  }
+  if let Selection::Chose(Some(filter)) = menu.run() {
+    filters.push(filter);
+  }
+
+  print!("{}", clear_screen());
+  println!("Press Enter to skip any of these.");
+  let min = prompt("At least how many minutes? ")?.parse().ok();
+  let max = prompt("At most how many minutes? ")?.parse().ok();
+  if min.is_some() || max.is_some() {
+    filters.push(Filter::Duration { min, max });
+  }
+  let equipment = prompt("What equipment do you have? (comma separated) ")?;
+  if !equipment.is_empty() {
+    filters.push(Filter::Equipment(
+      equipment.split(',').map(|e| e.trim().to_string()).collect(),
+    ));
+  }
+  let tag = prompt("Tag? ")?;
+  if !tag.is_empty() {
+    filters.push(Filter::Tag(tag));
+  }
+  if let Ok(days) = prompt("Not done in how many days? ")?.parse() {
+    filters.push(Filter::NotDoneIn(days));
+  }
+  let title = prompt("Title containing? ")?;
+  if !title.is_empty() {
+    filters.push(Filter::Title(title));
+  }
+
+  if filters.len() < 2 {
+    return Ok(Filter::All(filters));
+  }
+  let menu = Menu::new("Show workouts matching...")
+    .without_back()
+    .without_quit()
+    .item("All of these", || true)
+    .item("Any of these", || false);
+  Ok(match menu.run() {
+    Selection::Chose(false) => Filter::Any(filters),
+    _ => Filter::All(filters),
+  })
+}
+
+/// Show everything in a workout before starting it, either from the
+/// beginning or from a chosen set.
+fn preview_menu(
+  heading: String,
+  workout: &Workout,
+) -> Result<Menu<'_, Result<Next>>> {
+  let sessions = Session::load_all()?;
+  let last_done = history::last_done(&sessions, &workout.title);
+
+  let mut menu = Menu::new(heading);
+  for line in preview::preview(workout, last_done) {
+    menu = menu.line(line);
+  }
+  menu = menu.line("").item("Start", move || {
+    do_workout(workout, Start::Screen(0))?;
+    Ok(Next::Back)
+  });
+  if workout.sets.len() > 1 {
+    let title = workout.title.clone();
+    menu = menu.item("Start from set...", move || {
+      Ok(Next::Open(Page::StartFromSet(title.clone())))
+    });
+  }
+  Ok(
+    menu
+      .item("Edit", move || {
+        editor::edit(workout.clone(), Some(workout.title.clone()))?;
+        Ok(Next::Reload)
+      })
+      .item("Duplicate", move || {
+        print!("{}", clear_screen());
+        let title = prompt("Title for the copy: ")?;
+        if !title.is_empty() {
+          // saved first, so going straight back still keeps the copy.
+          match Edits::new()?.duplicate(&workout.title, &title) {
+            Ok(copy) => {
+              editor::edit(copy, Some(title))?;
+            }
+            Err(e) => {
+              println!("Couldn't copy it: {}", e);
+              pause()?;
+            }
+          }
+        }
+        Ok(Next::Reload)
+      })
+      .item("Rename", move || {
+        print!("{}", clear_screen());
+        let title = prompt(&format!("New title for {:?}: ", workout.title))?;
+        if !title.is_empty() {
+          if let Err(e) = Edits::new()?.rename(&workout.title, &title) {
+            println!("Couldn't rename it: {}", e);
+            pause()?;
+          }
+        }
+        Ok(Next::Reload)
+      })
+      .item("Delete", move || {
+        print!("{}", clear_screen());
+        match Edits::new()?.delete(&workout.title) {
+          Ok(()) => println!(
+            "Moved {:?} to the trash. It can be restored from Trash.",
+            workout.title
+          ),
+          Err(e) => println!("Couldn't delete it: {}", e),
+        }
+        pause()?;
+        Ok(Next::Reload)
+      }),
+  )
+}
+
+fn set_menu(heading: String, workout: &Workout) -> Menu<'_, Result<Next>> {
+  let mut menu = Menu::new(heading).line("Start from which set?");
+  for (n, set) in workout.sets.iter().enumerate() {
+    let names: Vec<&str> = set.iter().map(|e| e.name.as_str()).collect();
+    menu =
+      menu.item(format!("Set {}: {}", n + 1, names.join(", ")), move || {
+        do_workout(workout, Start::Set(n))?;
+        Ok(Next::Back)
+      });
+  }
+  menu
+}
+
+fn search_menu<'a>(
+  heading: String,
+  workouts: &'a [Workout],
+  query: &str,
+) -> Menu<'a, Result<Next>> {
+  let results = search::search(workouts, query);
+  let mut menu = Menu::new(heading).line(if results.is_empty() {
+    format!("No workouts match {:?}", query)
+  } else {
+    format!("Workouts matching {:?}", query)
+  });
+  for result in results {
+    let title = result.workout.title.clone();
+    menu = menu.item(&result.workout.title, move || {
+      Ok(Next::Open(Page::Preview(title.clone())))
+    });
+  }
+  menu
+}
+
+/// List the exercises with recorded lifts.
+fn history_menu(heading: String) -> Result<Menu<'static, Result<Next>>> {
+  let sessions = Session::load_all()?;
+  let names = history::lifted_exercises(&sessions);
+
+  let mut menu = Menu::new(heading);
+  if names.is_empty() {
+    menu = menu.line("No weights have been recorded yet.");
+  } else {
+    menu = menu.line("Choose an exercise");
+  }
+  for name in names {
+    menu = menu.item(name.clone(), move || {
+      show_progress(&name)?;
+      Ok(Next::Stay)
+    });
+  }
+  Ok(menu)
+}
+
+/// List the deleted workouts so they can be restored.
+fn trash_menu(heading: String) -> Result<Menu<'static, Result<Next>>> {
+  let titles = Edits::new()?.trashed()?;
+
+  let mut menu = Menu::new(heading).line(if titles.is_empty() {
+    "The trash is empty."
+  } else {
+    "Choose a workout to restore"
+  });
+  for title in titles {
+    menu = menu.item(title.clone(), move || {
+      print!("{}", clear_screen());
+      match Edits::new()?.restore(&title) {
+        Ok(()) => println!("Restored {:?}", title),
+        Err(e) => println!("Couldn't restore it: {}", e),
+      }
+      pause()?;
+      Ok(Next::Reload)
+    });
+  }
+  Ok(menu)
+}
+
+/// Show the lifts recorded for an exercise along with the suggested load for
+/// next time.
+fn show_progress(name: &str) -> Result<()> {
+  let sessions = Session::load_all()?;
+  let settings = Settings::load()?;
+
+  print!("{}", clear_screen());
+  println!("{}\n", name);
+  let progress = history::progress(&sessions, name);
+  for (session, lift) in progress.iter() {
+    println!(
+      "{}  {:>10}  {}",
+      session.started.format("%Y-%m-%d"),
+      lift.load().to_string(),
+      match lift.reps {
+        Some(reps) => format!("x {}", reps),
+        None => String::new(),
+      }
+    );
+  }
+  if let Some((_, last)) = progress.last() {
+    println!("\nNext time try: {}", settings.progression.suggest(last));
+  }
+  pause()
+}
+
+/// What to do with a generated workout.
+enum Generated {
+  Start,
+  Save,
+  Regenerate,
+}
+
+/// Ask for the constraints and generate a random workout from every exercise
+/// in the data directory.
+fn generate_workout(workouts: &[Workout]) -> Result<()> {
+  let mut menu = Menu::new("What type of workout?")
+    .without_back()
+    .without_quit()
+    .item("Any", || None);
+  for workout_type in ExerciseType::VALUES.iter() {
+    menu = menu.item(workout_type.to_string(), move || Some(*workout_type));
+  }
+  let workout_type = match menu.run() {
+    Selection::Chose(workout_type) => workout_type,
+    _ => return Ok(()),
+  };
+
+  let mut options = GeneratorOptions { workout_type, ..Default::default() };
+  if let Ok(sets) =
+    prompt(&format!("How many sets? [{}] ", options.sets))?.parse()
+  {
+    options.sets = sets;
+  }
+  options.equipment = prompt("What equipment do you have? (comma separated) ")?
+    .split(',')
+    .map(|e| e.trim().to_string())
+    .filter(|e| !e.is_empty())
+    .collect();
+  if let Ok(seed) = prompt("Seed? (Enter for a random one) ")?.parse() {
+    options.seed = seed;
+  }
+
+  loop {
+    let workout = match generator::generate(workouts, &options) {
+      Ok(workout) => workout,
+      Err(e) => {
+        println!("{}", e);
+        return pause();
+      }
+    };
+
+    let mut menu = Menu::new(&workout.title).without_quit();
+    for (n, set) in workout.sets.iter().enumerate() {
+      let names: Vec<&str> = set.iter().map(|e| e.name.as_str()).collect();
+      menu = menu.line(format!("Set {}: {}", n + 1, names.join(", ")));
+    }
+    let menu = menu
+      .item("Start", || Generated::Start)
+      .item("Save", || Generated::Save)
+      .item("Try another", || Generated::Regenerate);
+
+    match menu.run() {
+      Selection::Chose(Generated::Start) => {
+        return do_workout(&workout, Start::Screen(0))
+      }
+      Selection::Chose(Generated::Save) => {
+        workout.save()?;
+        println!("Saved {:?} - it will show up next time.", workout.title);
+        return pause();
+      }
+      Selection::Chose(Generated::Regenerate) => options.seed = rand::random(),
+      _ => return Ok(()),
+    }
+  }
+}
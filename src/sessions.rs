@@ -0,0 +1,127 @@
+use crate::lib::{
+  history::{Lift, Session},
+  settings::Settings,
+  timer::Timer,
+  util::*,
+  workout::{
+    checkpoint::Checkpoint, load::Load, prescription::Prescription, Start,
+    Workout,
+  },
+};
+use anyhow::Result;
+use chrono::Local;
+use workout_paths::data_path;
+
+/// Offer to resume the last workout if it was interrupted recently.
+pub fn offer_resume() -> Result<()> {
+  let checkpoint = match Checkpoint::load() {
+    Ok(Some(checkpoint)) => checkpoint,
+    Ok(None) => return Ok(()),
+    Err(e) => {
+      println!("Couldn't read the last checkpoint: {}", e);
+      return Checkpoint::clear();
+    }
+  };
+  let settings = Settings::load()?;
+  if checkpoint.age_mins() > settings.resume_max_age_mins {
+    return Checkpoint::clear();
+  }
+  let (workouts, _) = Workout::load_dir(&data_path())?;
+  let workout = match workouts.iter().find(|w| w.title == checkpoint.title) {
+    Some(workout) => workout,
+    None => return Checkpoint::clear(),
+  };
+
+  let answer = prompt(&format!(
+    "{} was interrupted {} minutes ago after {}. Resume it? (y/n) ",
+    checkpoint.title,
+    checkpoint.age_mins(),
+    checkpoint.stats.elapsed_secs.as_time()
+  ))?;
+  if answer.eq_ignore_ascii_case("y") {
+    resume_workout(workout, checkpoint)
+  } else {
+    Checkpoint::clear()
+  }
+}
+
+/// Run a workout from the given start and record the session in the
+/// history.
+pub fn do_workout(workout: &Workout, start: Start) -> Result<()> {
+  resume_workout(workout, Checkpoint::new(workout, start, Local::now()))
+}
+
+/// Carry on with a workout from a checkpoint and record the session in the
+/// history. A resumed session replaces the one saved when it was interrupted.
+pub fn resume_workout(workout: &Workout, checkpoint: Checkpoint) -> Result<()> {
+  let mut session = Session::new(&workout.title, checkpoint.started);
+  session.stats = workout.resume(checkpoint);
+  show_summary(&mut session)?;
+  record_session(workout, session)
+}
+
+/// Show how the workout went and ask how hard it felt.
+fn show_summary(session: &mut Session) -> Result<()> {
+  let stats = &session.stats;
+  print!("{}", clear_screen());
+  println!("{}\n", session.title);
+  println!("Active time:         {}", stats.active_secs().as_time());
+  println!("Total time:          {}", stats.elapsed_secs.as_time());
+  println!("Intervals completed: {}", stats.completed());
+  println!("Intervals skipped:   {}", stats.skipped());
+  println!("Overtime:            {}\n", stats.overtime_secs().as_time());
+
+  session.rpe = loop {
+    let input = prompt("How hard was it from 1 to 10? (Enter to skip) ")?;
+    if input.is_empty() {
+      break None;
+    }
+    match input.parse() {
+      Ok(rpe) if (1..=10).contains(&rpe) => break Some(rpe),
+      _ => println!("Please enter a number from 1 to 10."),
+    }
+  };
+  session.note = prompt("Any notes? ")?;
+
+  Ok(())
+}
+
+/// Ask for the weights lifted for every exercise with a prescribed load, then
+/// save the session.
+fn record_session(workout: &Workout, mut session: Session) -> Result<()> {
+  let loaded: Vec<_> =
+    workout.exercises().into_iter().filter(|e| e.load.is_some()).collect();
+
+  if !loaded.is_empty() {
+    print!("{}", clear_screen());
+    println!("What did you lift? Press Enter to keep the prescribed load or");
+    println!("type - to skip an exercise.\n");
+  }
+  for exercise in loaded {
+    let prescribed = exercise.load.unwrap();
+    let input = prompt(&format!("{} [{}]: ", exercise.name, prescribed))?;
+    let load = match input.as_str() {
+      "-" => continue,
+      "" => prescribed,
+      input => match Load::parse(input, prescribed.unit) {
+        Some(load) => load,
+        None => {
+          println!("Couldn't read {:?} as a weight, skipping.", input);
+          continue;
+        }
+      },
+    };
+    let prescribed_reps = match exercise.prescription {
+      Prescription::Reps { count, .. } => Some(count),
+      _ => None,
+    };
+    let input = prompt(&match prescribed_reps {
+      Some(count) => format!("  reps [{}]: ", count),
+      None => "  reps: ".to_string(),
+    })?;
+    let reps = input.parse().ok().or(prescribed_reps);
+    session.lifts.push(Lift::new(&exercise.name, load, reps));
+  }
+
+  session.save()
+}
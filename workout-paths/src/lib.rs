@@ -7,6 +7,12 @@ pub const CONFIG_DIR: &str = "workouts";
 pub const DATA_DIR: &str = "data";
 pub const IMPORT_DIR: &str = "import";
 pub const SOUNDS_DIR: &str = "sounds";
+pub const HISTORY_DIR: &str = "history";
+pub const STATE_DIR: &str = "state";
+pub const SETTINGS_FILE: &str = "settings.yml";
+pub const PROCESSED_DIR: &str = "processed";
+pub const FAILED_DIR: &str = "failed";
+pub const TRASH_DIR: &str = "trash";
 const CONFIG_HOME_ENV_VAR: &str = "XDG_CONFIG_HOME";
 
 pub enum Source {
@@ -39,14 +45,41 @@ pub fn data_path() -> PathBuf {
   config_path().join(DATA_DIR)
 }
 
+/// Where deleted workouts are kept so they can be restored.
+pub fn trash_path() -> PathBuf {
+  data_path().join(TRASH_DIR)
+}
+
 pub fn import_path() -> PathBuf {
   config_path().join(IMPORT_DIR)
 }
 
+/// Where imported files are moved once they're imported.
+pub fn processed_path() -> PathBuf {
+  import_path().join(PROCESSED_DIR)
+}
+
+/// Where import files that couldn't be imported are moved, along with why.
+pub fn failed_path() -> PathBuf {
+  import_path().join(FAILED_DIR)
+}
+
 pub fn sounds_path() -> PathBuf {
   config_path().join(SOUNDS_DIR)
 }
 
+pub fn history_path() -> PathBuf {
+  config_path().join(HISTORY_DIR)
+}
+
+pub fn state_path() -> PathBuf {
+  config_path().join(STATE_DIR)
+}
+
+pub fn settings_path() -> PathBuf {
+  config_path().join(SETTINGS_FILE)
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -60,13 +93,43 @@ mod tests {
     assert_eq!(data_path().file_name().unwrap(), DATA_DIR);
   }
 
+  #[test]
+  fn it_generates_trash_path_with_trash_dir() {
+    assert_eq!(trash_path().file_name().unwrap(), TRASH_DIR);
+  }
+
   #[test]
   fn it_generates_import_path_with_import_dir() {
     assert_eq!(import_path().file_name().unwrap(), IMPORT_DIR);
   }
 
+  #[test]
+  fn it_generates_processed_path_with_processed_dir() {
+    assert_eq!(processed_path().file_name().unwrap(), PROCESSED_DIR);
+  }
+
+  #[test]
+  fn it_generates_failed_path_with_failed_dir() {
+    assert_eq!(failed_path().file_name().unwrap(), FAILED_DIR);
+  }
+
   #[test]
   fn it_generates_sounds_path_with_sounds_dir() {
     assert_eq!(sounds_path().file_name().unwrap(), SOUNDS_DIR);
   }
+
+  #[test]
+  fn it_generates_history_path_with_history_dir() {
+    assert_eq!(history_path().file_name().unwrap(), HISTORY_DIR);
+  }
+
+  #[test]
+  fn it_generates_state_path_with_state_dir() {
+    assert_eq!(state_path().file_name().unwrap(), STATE_DIR);
+  }
+
+  #[test]
+  fn it_generates_settings_path_with_settings_file() {
+    assert_eq!(settings_path().file_name().unwrap(), SETTINGS_FILE);
+  }
 }